@@ -0,0 +1,91 @@
+use super::{is_hidden, ArchiveError, AudioFormat};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Outcome of a garbage-collection pass over the sounds root
+#[derive(Debug, Default)]
+pub struct GcReport {
+    /// Directories removed (or that would be removed in a dry run)
+    pub removed: Vec<PathBuf>,
+
+    /// Directories with no audio that were left in place because they contain other files
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Remove category directories under `root` that contain no supported audio files.
+///
+/// Only directories that are completely empty are deleted. Directories holding
+/// unsupported files are reported in `skipped`. With `recursive`, nested
+/// directories are cleaned bottom-up so a tree of empty directories is removed
+/// entirely.
+pub fn collect_garbage(
+    root: &Path,
+    recursive: bool,
+    dry_run: bool,
+) -> Result<GcReport, ArchiveError> {
+    let mut report = GcReport::default();
+
+    for entry in fs::read_dir(root)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if is_hidden(&entry.file_name()) || !path.is_dir() {
+            continue;
+        }
+
+        clean_dir(&path, recursive, dry_run, &mut report)?;
+    }
+
+    Ok(report)
+}
+
+/// Clean a single directory, returning true if it was (or would be) removed
+fn clean_dir(
+    dir: &Path,
+    recursive: bool,
+    dry_run: bool,
+    report: &mut GcReport,
+) -> Result<bool, ArchiveError> {
+    let mut has_audio = false;
+    let mut has_other = false;
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if path.is_dir() {
+            if recursive
+                && !is_hidden(&entry.file_name())
+                && clean_dir(&path, recursive, dry_run, report)?
+            {
+                continue;
+            }
+            has_other = true;
+        } else if path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(AudioFormat::from_extension)
+            .is_some()
+        {
+            has_audio = true;
+        } else {
+            has_other = true;
+        }
+    }
+
+    if has_audio {
+        return Ok(false);
+    }
+
+    if has_other {
+        report.skipped.push(dir.to_path_buf());
+        return Ok(false);
+    }
+
+    if !dry_run {
+        fs::remove_dir(dir)?;
+    }
+    report.removed.push(dir.to_path_buf());
+
+    Ok(true)
+}
@@ -0,0 +1,114 @@
+use super::{ArchiveError, AudioFormat};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Outcome of importing files into a category
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Files copied, linked, or moved into the category
+    pub imported: Vec<PathBuf>,
+
+    /// Files skipped because the category already has a file with that name
+    pub existing: Vec<PathBuf>,
+
+    /// Files skipped because they aren't a supported audio format
+    pub unsupported: Vec<PathBuf>,
+
+    /// Files that don't exist
+    pub missing: Vec<PathBuf>,
+}
+
+/// How `import_files` puts each file into the category
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    Copy,
+
+    /// Symlink to the original file instead of copying it
+    Link,
+
+    /// Move the file, removing the original
+    Move,
+}
+
+impl ImportMode {
+    /// Past-tense verb for summaries, e.g. "Copied"
+    pub fn verb(&self) -> &'static str {
+        match self {
+            Self::Copy => "Copied",
+            Self::Link => "Linked",
+            Self::Move => "Moved",
+        }
+    }
+}
+
+/// Copy, symlink, or move supported audio files into `dest_dir`, creating it
+/// if needed. Existing files in the destination are left alone. With `dry_run`
+/// the report says what would be imported but nothing is touched.
+pub fn import_files(
+    files: &[PathBuf],
+    dest_dir: &Path,
+    mode: ImportMode,
+    dry_run: bool,
+) -> Result<ImportReport, ArchiveError> {
+    let mut report = ImportReport::default();
+    if !dry_run {
+        fs::create_dir_all(dest_dir)?;
+    }
+
+    for file in files {
+        let supported = file
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(AudioFormat::from_extension)
+            .is_some();
+
+        let Some(file_name) = file.file_name().filter(|_| supported) else {
+            report.unsupported.push(file.clone());
+            continue;
+        };
+
+        if !file.is_file() {
+            report.missing.push(file.clone());
+            continue;
+        }
+
+        let dest = dest_dir.join(file_name);
+        if dest.exists() {
+            report.existing.push(file.clone());
+            continue;
+        }
+
+        match mode {
+            _ if dry_run => {}
+            ImportMode::Copy => {
+                fs::copy(file, &dest)?;
+            }
+            ImportMode::Link => symlink(&fs::canonicalize(file)?, &dest)?,
+            ImportMode::Move => move_file(file, &dest)?,
+        }
+        report.imported.push(file.clone());
+    }
+
+    Ok(report)
+}
+
+/// Rename `from` to `to`, falling back to copy and delete across filesystems
+pub(super) fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    fs::copy(from, to)?;
+    fs::remove_file(from)
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(target, link)
+}
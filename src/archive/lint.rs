@@ -0,0 +1,99 @@
+use super::{ArchiveError, SoundArchive};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// What `lint_archive` does about files it can't read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionFix {
+    /// Only report unreadable files
+    Report,
+
+    /// Report the files that would be fixed without changing them
+    DryRun,
+
+    /// Add read permission to unreadable files
+    Apply,
+}
+
+/// Problems found by a lint pass over the archive
+#[derive(Debug, Default)]
+pub struct LintReport {
+    /// Sound files the current user can't read (left as-is unless fixed)
+    pub unreadable: Vec<PathBuf>,
+
+    /// Files whose permissions were fixed (or would be, in a dry run)
+    pub fixed: Vec<PathBuf>,
+}
+
+impl LintReport {
+    pub fn is_clean(&self) -> bool {
+        self.unreadable.is_empty()
+    }
+}
+
+/// Check every sound in the archive and optionally repair the problems found
+pub fn lint_archive(
+    archive: &SoundArchive,
+    fix: PermissionFix,
+) -> Result<LintReport, ArchiveError> {
+    let mut report = LintReport::default();
+
+    for sound in archive.categories.iter().flat_map(|c| &c.sounds) {
+        if is_readable(&sound.path)? {
+            continue;
+        }
+
+        report.unreadable.push(sound.path.clone());
+
+        match fix {
+            PermissionFix::Report => {}
+            PermissionFix::DryRun => report.fixed.push(sound.path.clone()),
+            PermissionFix::Apply => {
+                make_readable(&sound.path)?;
+                report.fixed.push(sound.path.clone());
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Whether the current user can read the file. On Unix a file whose owner has no
+/// read bit counts as unreadable even when root could open it anyway.
+#[cfg(unix)]
+fn is_readable(path: &Path) -> io::Result<bool> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(path)?.permissions().mode();
+    if mode & 0o400 == 0 {
+        return Ok(false);
+    }
+
+    match File::open(path) {
+        Ok(_) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Windows has no read bits to check, so flag read-only files instead
+#[cfg(not(unix))]
+fn is_readable(path: &Path) -> io::Result<bool> {
+    Ok(!fs::metadata(path)?.permissions().readonly())
+}
+
+#[cfg(unix)]
+fn make_readable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(path)?.permissions().mode();
+    fs::set_permissions(path, fs::Permissions::from_mode(mode | 0o644))
+}
+
+#[cfg(not(unix))]
+fn make_readable(path: &Path) -> io::Result<()> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_readonly(false);
+    fs::set_permissions(path, permissions)
+}
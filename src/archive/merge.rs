@@ -0,0 +1,159 @@
+use super::{import_files, ArchiveError, ImportMode, SoundArchive};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Outcome of merging one archive into another
+#[derive(Debug, Default)]
+pub struct MergeReport {
+    pub categories: usize,
+    pub added: usize,
+    pub skipped: usize,
+}
+
+/// Copy every category and sound from `source` into the first of `roots`.
+///
+/// Category directories are created as needed. Sounds whose filename already
+/// exists in the destination category are skipped unless `overwrite` is set,
+/// and a sound is never copied onto itself (e.g. through a symlinked category).
+/// A source that is, or sits inside, one of `roots` is refused.
+pub fn merge_archive(
+    source: &SoundArchive,
+    roots: &[PathBuf],
+    overwrite: bool,
+    dry_run: bool,
+) -> Result<MergeReport, ArchiveError> {
+    let dest_root = roots.first().ok_or(ArchiveError::NoRoots)?;
+    let source_root = fs::canonicalize(source.root())?;
+    if let Some(root) = roots
+        .iter()
+        .find(|root| fs::canonicalize(root).is_ok_and(|root| source_root.starts_with(root)))
+    {
+        return Err(ArchiveError::SourceInsideArchive {
+            path: source.root().to_path_buf(),
+            root: root.clone(),
+        });
+    }
+
+    let mut report = MergeReport::default();
+
+    for cat in &source.categories {
+        let dest_dir = dest_root.join(&cat.name);
+        let mut new_files = Vec::new();
+
+        for sound in &cat.sounds {
+            let Some(file_name) = sound.path.file_name() else {
+                continue;
+            };
+            let dest = dest_dir.join(file_name);
+
+            if !dest.exists() {
+                new_files.push(sound.path.clone());
+                continue;
+            }
+            if !overwrite || is_same_file(&sound.path, &dest) {
+                report.skipped += 1;
+                continue;
+            }
+
+            // Replace the entry rather than writing through it, so a linked
+            // sound doesn't overwrite the file it points to
+            if !dry_run {
+                fs::remove_file(&dest)?;
+                fs::copy(&sound.path, &dest)?;
+            }
+            report.added += 1;
+        }
+
+        let imported = import_files(&new_files, &dest_dir, ImportMode::Copy, dry_run)?;
+        report.added += imported.imported.len();
+        report.skipped +=
+            imported.existing.len() + imported.unsupported.len() + imported.missing.len();
+        report.categories += 1;
+    }
+
+    Ok(report)
+}
+
+/// Whether both paths resolve to the same file
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::ArchiveLoadOptions;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cue-merge-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn load(root: &Path) -> SoundArchive {
+        SoundArchive::load(&[root.to_path_buf()], ArchiveLoadOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn merge_refuses_a_source_inside_the_archive() {
+        let root = scratch("inside");
+        fs::create_dir_all(root.join("alerts")).unwrap();
+        fs::write(root.join("alerts/beep.wav"), b"beep").unwrap();
+
+        for source in [root.clone(), root.join("alerts")] {
+            assert!(matches!(
+                merge_archive(&load(&source), std::slice::from_ref(&root), true, false),
+                Err(ArchiveError::SourceInsideArchive { .. })
+            ));
+        }
+        assert_eq!(fs::read(root.join("alerts/beep.wav")).unwrap(), b"beep");
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn merge_never_copies_a_sound_onto_itself() {
+        let source = scratch("source");
+        let dest = scratch("dest");
+        fs::create_dir_all(source.join("alerts")).unwrap();
+        fs::write(source.join("alerts/beep.wav"), b"beep").unwrap();
+        std::os::unix::fs::symlink(source.join("alerts"), dest.join("alerts")).unwrap();
+
+        let report =
+            merge_archive(&load(&source), std::slice::from_ref(&dest), true, false).unwrap();
+        assert_eq!((report.added, report.skipped), (0, 1));
+        assert_eq!(fs::read(source.join("alerts/beep.wav")).unwrap(), b"beep");
+
+        fs::remove_dir_all(source).unwrap();
+        fs::remove_dir_all(dest).unwrap();
+    }
+
+    #[test]
+    fn merge_overwrites_only_with_overwrite() {
+        let source = scratch("newer");
+        let dest = scratch("older");
+        fs::create_dir_all(source.join("alerts")).unwrap();
+        fs::create_dir_all(dest.join("alerts")).unwrap();
+        fs::write(source.join("alerts/beep.wav"), b"new").unwrap();
+        fs::write(source.join("alerts/chime.wav"), b"chime").unwrap();
+        fs::write(dest.join("alerts/beep.wav"), b"old").unwrap();
+
+        let report =
+            merge_archive(&load(&source), std::slice::from_ref(&dest), false, false).unwrap();
+        assert_eq!((report.added, report.skipped), (1, 1));
+        assert_eq!(fs::read(dest.join("alerts/beep.wav")).unwrap(), b"old");
+
+        let report =
+            merge_archive(&load(&source), std::slice::from_ref(&dest), true, false).unwrap();
+        assert_eq!((report.added, report.skipped), (2, 0));
+        assert_eq!(fs::read(dest.join("alerts/beep.wav")).unwrap(), b"new");
+
+        fs::remove_dir_all(source).unwrap();
+        fs::remove_dir_all(dest).unwrap();
+    }
+}
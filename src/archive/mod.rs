@@ -1,13 +1,54 @@
+mod gc;
+mod import;
+mod lint;
+mod merge;
+mod snapshot;
+mod validate;
+mod watch;
+
+pub use gc::collect_garbage;
+pub use import::{import_files, ImportMode};
+pub use lint::{lint_archive, PermissionFix};
+pub use merge::merge_archive;
+pub use snapshot::{create_snapshot, restore_snapshot};
+pub use validate::{quarantine, validate_categories};
+pub use watch::watch_new_files;
+
+use crate::playback;
+use crate::state;
+use clap::ValueEnum;
+use glob::{MatchOptions, Pattern};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
 use std::{fs, io};
 use thiserror::Error;
+use tracing::{debug, warn};
+
+/// Directory levels loaded as categories when no depth is configured
+pub const DEFAULT_CATEGORY_DEPTH: usize = 2;
+
+/// Optional per-category file mapping sound names to selection weights
+const WEIGHTS_FILE: &str = "weights.toml";
+
+/// Optional per-category metadata file
+const METADATA_FILE: &str = "category.toml";
 
 #[derive(Debug, Error)]
 pub enum ArchiveError {
     #[error("Archive path does not exist: {0}")]
     NotFound(PathBuf),
 
+    #[error("No sound directories configured")]
+    NoRoots,
+
     #[error("Archive path is not a directory: {0}")]
     NotDirectory(PathBuf),
 
@@ -20,11 +61,46 @@ pub enum ArchiveError {
     #[error("Category is empty: {0}")]
     EmptyCategory(String),
 
+    #[error("Category '{category}' has {found} sounds, {required} required")]
+    InsufficientSounds {
+        category: String,
+        found: usize,
+        required: usize,
+    },
+
+    #[error("Failed to parse {path}: {message}")]
+    WeightsParseError { path: PathBuf, message: String },
+
+    #[error("Failed to parse {path}: {message}")]
+    MetadataParseError { path: PathBuf, message: String },
+
     #[error("Sound not found: {0}")]
     SoundNotFound(String),
+
+    #[error("Unsupported audio format: {0} (expected mp3, wav, ogg, or flac)")]
+    InvalidFormat(PathBuf),
+
+    #[error("{0} already exists")]
+    AlreadyExists(PathBuf),
+
+    #[error("Invalid name: {0}")]
+    InvalidName(String),
+
+    #[error("{path} is inside the sound archive at {root}; merge from a separate directory")]
+    SourceInsideArchive { path: PathBuf, root: PathBuf },
+
+    #[error("Invalid snapshot: {0}")]
+    InvalidSnapshot(String),
+
+    #[error("Snapshot failed verification, missing or corrupt: {0}")]
+    CorruptSnapshot(String),
+
+    #[error("Failed to watch directory: {0}")]
+    WatchError(#[from] notify::Error),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
 pub enum AudioFormat {
     Mp3,
     Wav,
@@ -42,6 +118,35 @@ impl AudioFormat {
             _ => None,
         }
     }
+
+    /// Detect the format from a file's leading bytes, ignoring its extension.
+    /// Needs at least the first 12 bytes for WAV; returns `None` if unrecognized.
+    pub fn from_magic_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some(Self::Wav),
+            [b'O', b'g', b'g', b'S', ..] => Some(Self::Ogg),
+            [b'f', b'L', b'a', b'C', ..] => Some(Self::Flac),
+            [b'I', b'D', b'3', ..] => Some(Self::Mp3),
+            // Bare MPEG audio frame sync
+            [0xFF, b, ..] if b & 0xE0 == 0xE0 => Some(Self::Mp3),
+            _ => None,
+        }
+    }
+
+    /// Whether the format discards audio information when encoding
+    pub fn is_lossy(&self) -> bool {
+        matches!(self, Self::Mp3 | Self::Ogg)
+    }
+
+    /// Lowercase name of the format, matching its file extension
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Mp3 => "mp3",
+            Self::Wav => "wav",
+            Self::Ogg => "ogg",
+            Self::Flac => "flac",
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -49,19 +154,165 @@ pub struct SoundFile {
     pub name: String,
     pub path: PathBuf,
     pub format: AudioFormat,
+
+    /// Playback length, probed on first use by `duration()`
+    duration: OnceLock<Option<Duration>>,
+
+    /// File size in bytes, read on first use by `size()`
+    size: OnceLock<u64>,
+}
+
+impl SoundFile {
+    /// Playback length of the sound, decoding its header the first time it's asked for.
+    /// `None` if the file can't be decoded or its format doesn't report a length.
+    pub fn duration(&self) -> Option<Duration> {
+        *self
+            .duration
+            .get_or_init(|| playback::probe_duration(&self.path))
+    }
+
+    /// File size in bytes, read from filesystem metadata the first time it's asked for.
+    /// 0 if the file can't be read.
+    pub fn size(&self) -> u64 {
+        *self
+            .size
+            .get_or_init(|| fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0))
+    }
+}
+
+/// Settings from a category's `category.toml`; every field is optional
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CategoryMetadata {
+    /// Name to show in output; an alias in the config takes precedence
+    pub display_name: Option<String>,
+
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+
+    /// How often `cue play --balance-categories` picks this category relative
+    /// to others (2.0 = twice as often)
+    pub weight: f32,
+
+    /// Disabled categories, and the categories nested in them, aren't loaded
+    pub enabled: bool,
+
+    /// Order of this category's sounds, instead of the config's `sort`
+    pub sort: Option<SortOrder>,
+}
+
+impl Default for CategoryMetadata {
+    fn default() -> Self {
+        Self {
+            display_name: None,
+            description: None,
+            tags: Vec::new(),
+            weight: 1.0,
+            enabled: true,
+            sort: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Category {
+    /// Slash-separated path below the archive root (e.g. `ambient/rain`)
     pub name: String,
+
+    /// Human-readable name for output, from the config's `[aliases]` table or
+    /// `category.toml`. Same as `name` when neither sets one.
+    pub display_name: String,
+
+    /// Full name of the enclosing category, for nested categories
+    pub parent: Option<String>,
+
     pub path: PathBuf,
     pub sounds: Vec<SoundFile>,
+
+    /// Selection weights by sound name, from the category's `weights.toml`.
+    /// Sounds not listed weigh 1.0; empty means uniform selection.
+    pub weights: HashMap<String, f64>,
+
+    /// Settings from the category's `category.toml`, defaults if it has none
+    pub metadata: CategoryMetadata,
 }
 
 impl Category {
-    /// Pick a random sound from this category
+    /// Last component of the category name (`rain` for `ambient/rain`)
+    pub fn leaf_name(&self) -> &str {
+        self.name.rsplit('/').next().unwrap_or(&self.name)
+    }
+
+    /// Pick a random sound from this category, biased by `weights.toml` if present
     pub fn random(&self) -> Option<&SoundFile> {
-        self.sounds.choose(&mut rand::thread_rng())
+        self.random_excluding(&[], &[])
+    }
+
+    /// Like `random`, but never picks a sound whose name is in `exclude`
+    /// (case-insensitive) or whose format is in `formats`. Each file is judged
+    /// on its own, so excluding `mp3` keeps `beep.wav` even if `beep.mp3` exists.
+    /// `None` if every sound is excluded.
+    pub fn random_excluding<'a>(
+        &'a self,
+        exclude: &[&str],
+        formats: &[AudioFormat],
+    ) -> Option<&'a SoundFile> {
+        let candidates: Vec<&SoundFile> = self
+            .sounds
+            .iter()
+            .filter(|s| {
+                !formats.contains(&s.format)
+                    && !exclude.iter().any(|e| e.eq_ignore_ascii_case(&s.name))
+            })
+            .collect();
+
+        if self.weights.is_empty() {
+            return candidates.choose(&mut rand::thread_rng()).copied();
+        }
+
+        let weights = candidates
+            .iter()
+            .map(|s| self.weights.get(&s.name).copied().unwrap_or(1.0));
+        let dist = WeightedIndex::new(weights).ok()?;
+        Some(candidates[dist.sample(&mut rand::thread_rng())])
+    }
+
+    /// Pick `count` sounds to play in turn. With `replace` each is an independent
+    /// `random()` pick; otherwise no sound repeats, which needs at least `count`
+    /// sounds unless `wrap` starts a fresh shuffle once every sound has been used.
+    pub fn random_many(
+        &self,
+        count: usize,
+        replace: bool,
+        wrap: bool,
+    ) -> Result<Vec<&SoundFile>, ArchiveError> {
+        if self.sounds.is_empty() {
+            return Err(ArchiveError::EmptyCategory(self.name.clone()));
+        }
+
+        if replace {
+            return (0..count)
+                .map(|_| self.random())
+                .collect::<Option<_>>()
+                .ok_or_else(|| ArchiveError::EmptyCategory(self.name.clone()));
+        }
+
+        if count > self.sounds.len() && !wrap {
+            return Err(ArchiveError::InsufficientSounds {
+                category: self.name.clone(),
+                found: self.sounds.len(),
+                required: count,
+            });
+        }
+
+        let mut rng = rand::thread_rng();
+        let mut picked = Vec::with_capacity(count);
+        while picked.len() < count {
+            let batch = (count - picked.len()).min(self.sounds.len());
+            picked.extend(self.sounds.choose_multiple(&mut rng, batch));
+        }
+
+        Ok(picked)
     }
 
     /// Get a sound by filename (case-insensitive, extension optional)
@@ -76,119 +327,696 @@ impl Category {
                     .unwrap_or(false)
         })
     }
+
+    /// Get all sounds whose name or filename matches a shell glob pattern
+    /// (`*`, `?`, `[abc]`), case-insensitively. Invalid patterns match literally.
+    pub fn sounds_matching(&self, pattern: &str) -> Vec<&SoundFile> {
+        let pattern = Pattern::new(pattern)
+            .unwrap_or_else(|_| Pattern::new(&Pattern::escape(pattern)).expect("escaped pattern"));
+
+        let options = MatchOptions {
+            case_sensitive: false,
+            ..MatchOptions::new()
+        };
+
+        self.sounds
+            .iter()
+            .filter(|s| {
+                pattern.matches_with(&s.name, options)
+                    || s.path
+                        .file_name()
+                        .and_then(|f| f.to_str())
+                        .map(|f| pattern.matches_with(f, options))
+                        .unwrap_or(false)
+            })
+            .collect()
+    }
+}
+
+/// How `SoundArchive::load` walks the roots
+#[derive(Debug, Clone, Copy)]
+pub struct ArchiveLoadOptions {
+    /// Directory levels below each root to load as categories
+    pub depth: usize,
+
+    /// Load symlinked category directories and sound files. Turn off to keep
+    /// the archive from reaching outside its roots.
+    pub follow_symlinks: bool,
+
+    /// Order of categories, and of sounds in categories without their own `sort`
+    pub sort: SortOrder,
+
+    /// Seed for `SortOrder::Random`
+    pub seed: u64,
+}
+
+impl Default for ArchiveLoadOptions {
+    fn default() -> Self {
+        Self {
+            depth: DEFAULT_CATEGORY_DEPTH,
+            follow_symlinks: true,
+            sort: SortOrder::default(),
+            seed: session_seed(),
+        }
+    }
+}
+
+/// How categories and the sounds in them are ordered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// A to Z, ignoring case
+    #[default]
+    Alphabetical,
+
+    /// Z to A, ignoring case
+    Reverse,
+
+    /// Shuffled, with the order fixed by the seed
+    Random,
+
+    /// Most recently modified first
+    Modified,
+}
+
+impl SortOrder {
+    /// Sort `items`, keyed by the name and path `key` returns for each
+    fn apply<T>(self, items: &mut [T], seed: u64, key: impl Fn(&T) -> (&str, &Path)) {
+        items.sort_by_cached_key(|item| key(item).0.to_lowercase());
+
+        match self {
+            Self::Alphabetical => {}
+            Self::Reverse => items.reverse(),
+            Self::Random => items.shuffle(&mut StdRng::seed_from_u64(seed)),
+            Self::Modified => items.sort_by_cached_key(|item| {
+                let modified = fs::metadata(key(item).1).and_then(|m| m.modified());
+                std::cmp::Reverse(modified.unwrap_or(SystemTime::UNIX_EPOCH))
+            }),
+        }
+    }
+}
+
+/// Random seed picked once per process, so `SortOrder::Random` gives the same
+/// order on every load in a run (e.g. `--watch` reloads) but a new one each run
+pub fn session_seed() -> u64 {
+    static SEED: OnceLock<u64> = OnceLock::new();
+    *SEED.get_or_init(rand::random)
 }
 
 #[derive(Debug)]
 pub struct SoundArchive {
-    pub path: PathBuf,
+    /// Root directories the archive was loaded from, primary first
+    pub paths: Vec<PathBuf>,
+
+    /// Options the archive was loaded with, reused when it's reloaded
+    pub options: ArchiveLoadOptions,
+
     pub categories: Vec<Category>,
+
+    /// Problems that didn't stop the load (e.g. a missing secondary root), for
+    /// the caller to report
+    pub warnings: Vec<String>,
 }
 
 impl SoundArchive {
-    /// Load and index an archive from the given path
-    pub fn load(path: impl AsRef<Path>) -> Result<Self, ArchiveError> {
-        let path = path.as_ref().to_path_buf();
+    /// Load and index an archive from one or more root directories, turning
+    /// directories up to `options.depth` levels below each root into categories.
+    ///
+    /// Nested directories are named by their slash path (e.g. `ambient/rain`);
+    /// a depth of 1 loads only top-level categories. Categories with the same
+    /// name in several roots are merged, keeping the first root's directory.
+    ///
+    /// The primary root must exist; a missing secondary root is skipped with a
+    /// warning so one unmounted drive doesn't make every command fail.
+    pub fn load(paths: &[PathBuf], options: ArchiveLoadOptions) -> Result<Self, ArchiveError> {
+        if paths.is_empty() {
+            return Err(ArchiveError::NoRoots);
+        }
+
+        let mut categories: Vec<Category> = Vec::new();
+        let mut warnings = Vec::new();
+
+        for (i, path) in paths.iter().enumerate() {
+            if !path.exists() {
+                if i == 0 {
+                    return Err(ArchiveError::NotFound(path.clone()));
+                }
+                warn!(path = %path.display(), "Skipping missing sound directory");
+                warnings.push(format!(
+                    "Sound directory {} does not exist; skipping it",
+                    path.display()
+                ));
+                continue;
+            }
 
-        if !path.exists() {
-            return Err(ArchiveError::NotFound(path));
+            if !path.is_dir() {
+                return Err(ArchiveError::NotDirectory(path.clone()));
+            }
+
+            let mut loaded = Vec::new();
+            load_categories(
+                path,
+                None,
+                options.depth,
+                options,
+                &mut loaded,
+                &mut warnings,
+            )?;
+
+            for cat in loaded {
+                match categories.iter_mut().find(|c| c.name == cat.name) {
+                    Some(existing) => {
+                        existing.weights.extend(cat.weights);
+                        existing.sounds.extend(cat.sounds);
+                    }
+                    None => categories.push(cat),
+                }
+            }
         }
 
-        if !path.is_dir() {
-            return Err(ArchiveError::NotDirectory(path));
+        // Sort once everything is merged; each category can override the order of its sounds
+        for cat in &mut categories {
+            let order = cat.metadata.sort.unwrap_or(options.sort);
+            // Mix in the name so categories aren't all shuffled the same way
+            let seed = options.seed ^ state::stable_hash(&cat.name);
+            order.apply(&mut cat.sounds, seed, |s| (&s.name, &s.path));
         }
+        options
+            .sort
+            .apply(&mut categories, options.seed, |c| (&c.name, &c.path));
+
+        debug!(
+            paths = ?paths,
+            categories = categories.len(),
+            "Loaded sound archive"
+        );
+
+        Ok(Self {
+            paths: paths.to_vec(),
+            options,
+            categories,
+            warnings,
+        })
+    }
 
-        let mut categories = Vec::new();
+    /// Primary root directory, where commands that add files write to. `load`
+    /// refuses an empty list of roots, so there is always one.
+    pub fn root(&self) -> &Path {
+        &self.paths[0]
+    }
+
+    /// Get a category by its full slash path, its leaf name if unambiguous, or
+    /// its display alias (case-insensitive)
+    pub fn category(&self, name: &str) -> Option<&Category> {
+        let name_lower = name.to_lowercase();
 
-        for entry in fs::read_dir(&path)? {
-            let entry = entry?;
-            let entry_path = entry.path();
+        if let Some(cat) = self.categories.iter().find(|c| c.name == name_lower) {
+            return Some(cat);
+        }
+
+        let mut leaf_matches = self
+            .categories
+            .iter()
+            .filter(|c| c.leaf_name() == name_lower);
+        if let (Some(cat), None) = (leaf_matches.next(), leaf_matches.next()) {
+            return Some(cat);
+        }
 
-            // Skip hidden directories
-            if entry
-                .file_name()
-                .to_str()
-                .map(|s| s.starts_with('.'))
-                .unwrap_or(false)
+        self.categories
+            .iter()
+            .find(|c| c.display_name.to_lowercase() == name_lower)
+    }
+
+    /// Set each category's display name from an alias table keyed by category
+    /// name (full slash path or leaf name, case-insensitive)
+    pub fn with_aliases(mut self, aliases: &BTreeMap<String, String>) -> Self {
+        let aliases: HashMap<String, &String> = aliases
+            .iter()
+            .map(|(name, alias)| (name.to_lowercase(), alias))
+            .collect();
+
+        for cat in &mut self.categories {
+            if let Some(alias) = aliases
+                .get(&cat.name)
+                .or_else(|| aliases.get(cat.leaf_name()))
             {
+                cat.display_name = alias.to_string();
+            }
+        }
+
+        self
+    }
+
+    /// Endless stream of random sounds taking each category in turn.
+    /// Yields an error for categories that don't exist or have no sounds.
+    pub fn interleaved_random<'a>(
+        &'a self,
+        categories: &[&str],
+    ) -> impl Iterator<Item = Result<&'a SoundFile, ArchiveError>> + 'a {
+        let names: Vec<String> = categories.iter().map(|c| c.to_string()).collect();
+
+        names.into_iter().cycle().map(move |name| {
+            let cat = self
+                .category(&name)
+                .ok_or_else(|| ArchiveError::CategoryNotFound(name.clone()))?;
+            cat.random().ok_or(ArchiveError::EmptyCategory(name))
+        })
+    }
+
+    /// Every sound whose canonical path starts with `root`, paired with its category.
+    /// The prefix is compared as a string, so partial directory names match too.
+    pub fn sounds_from_root<'a>(
+        &'a self,
+        root: &Path,
+    ) -> impl Iterator<Item = (&'a Category, &'a SoundFile)> + 'a {
+        let prefix = fs::canonicalize(root)
+            .or_else(|_| std::path::absolute(root))
+            .unwrap_or_else(|_| root.to_path_buf())
+            .to_string_lossy()
+            .into_owned();
+
+        self.categories
+            .iter()
+            .flat_map(|cat| cat.sounds.iter().map(move |sound| (cat, sound)))
+            .filter(move |(_, sound)| {
+                fs::canonicalize(&sound.path)
+                    .unwrap_or_else(|_| sound.path.clone())
+                    .to_string_lossy()
+                    .starts_with(&prefix)
+            })
+    }
+
+    /// Number of categories in the archive, including empty ones
+    pub fn category_count(&self) -> usize {
+        self.categories.len()
+    }
+
+    /// List all category names
+    pub fn category_names(&self) -> Vec<&str> {
+        self.categories.iter().map(|c| c.name.as_str()).collect()
+    }
+}
+
+/// Read each subdirectory of `dir` as a category, recursing until `depth` levels
+/// have been loaded. A malformed `weights.toml` or `category.toml` is reported in
+/// `warnings` and replaced by the defaults.
+fn load_categories(
+    dir: &Path,
+    parent: Option<&str>,
+    depth: usize,
+    options: ArchiveLoadOptions,
+    categories: &mut Vec<Category>,
+    warnings: &mut Vec<String>,
+) -> Result<(), ArchiveError> {
+    if depth == 0 {
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let entry_path = entry.path();
+
+        // Skip hidden directories
+        if is_hidden(&entry.file_name()) {
+            continue;
+        }
+
+        // Only process directories, including symlinked ones if allowed
+        if !is_entry_kind(&entry, options, |t| t.is_dir())? {
+            continue;
+        }
+
+        let leaf = entry
+            .file_name()
+            .to_str()
+            .unwrap_or("unknown")
+            .to_lowercase();
+
+        let category_name = match parent {
+            Some(parent) => format!("{}/{}", parent, leaf),
+            None => leaf,
+        };
+
+        let metadata = or_default(load_metadata(&entry_path.join(METADATA_FILE)), warnings)?;
+        if !metadata.enabled {
+            debug!(category = %category_name, "Skipping disabled category");
+            continue;
+        }
+
+        let mut sounds = Vec::new();
+
+        for sound_entry in fs::read_dir(&entry_path)? {
+            let sound_entry = sound_entry?;
+            let sound_path = sound_entry.path();
+
+            // Skip hidden files
+            if is_hidden(&sound_entry.file_name()) {
                 continue;
             }
 
-            // Only process directories
-            if !entry_path.is_dir() {
+            // Only process files with supported extensions
+            if !is_entry_kind(&sound_entry, options, |t| t.is_file())? {
                 continue;
             }
 
-            let category_name = entry
-                .file_name()
-                .to_str()
-                .unwrap_or("unknown")
-                .to_lowercase();
+            let extension = sound_path
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or("");
+
+            if let Some(format) = AudioFormat::from_extension(extension) {
+                let name = sound_path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("unknown")
+                    .to_string();
+
+                sounds.push(SoundFile {
+                    name,
+                    path: sound_path,
+                    format,
+                    duration: OnceLock::new(),
+                    size: OnceLock::new(),
+                });
+            }
+        }
 
-            let mut sounds = Vec::new();
+        let weights = or_default(load_weights(&entry_path.join(WEIGHTS_FILE)), warnings)?;
+
+        load_categories(
+            &entry_path,
+            Some(&category_name),
+            depth - 1,
+            options,
+            categories,
+            warnings,
+        )?;
+
+        categories.push(Category {
+            display_name: metadata
+                .display_name
+                .clone()
+                .unwrap_or_else(|| category_name.clone()),
+            name: category_name,
+            parent: parent.map(str::to_string),
+            path: entry_path,
+            sounds,
+            weights,
+            metadata,
+        });
+    }
 
-            for sound_entry in fs::read_dir(&entry_path)? {
-                let sound_entry = sound_entry?;
-                let sound_path = sound_entry.path();
+    Ok(())
+}
 
-                // Skip hidden files
-                if sound_entry
-                    .file_name()
-                    .to_str()
-                    .map(|s| s.starts_with('.'))
-                    .unwrap_or(false)
-                {
-                    continue;
-                }
+/// Whether a directory entry is of the kind `check` tests for. Symlinks are
+/// judged by their target, or skipped when `follow_symlinks` is off; links
+/// whose target is missing are skipped either way.
+fn is_entry_kind(
+    entry: &fs::DirEntry,
+    options: ArchiveLoadOptions,
+    check: impl Fn(&fs::FileType) -> bool,
+) -> Result<bool, ArchiveError> {
+    let file_type = entry.file_type()?;
+    if !file_type.is_symlink() {
+        return Ok(check(&file_type));
+    }
 
-                // Only process files with supported extensions
-                if !sound_path.is_file() {
-                    continue;
-                }
+    if !options.follow_symlinks {
+        debug!(path = %entry.path().display(), "Skipping symlink");
+        return Ok(false);
+    }
 
-                let extension = sound_path
-                    .extension()
-                    .and_then(|e| e.to_str())
-                    .unwrap_or("");
-
-                if let Some(format) = AudioFormat::from_extension(extension) {
-                    let name = sound_path
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("unknown")
-                        .to_string();
-
-                    sounds.push(SoundFile {
-                        name,
-                        path: sound_path,
-                        format,
-                    });
+    match fs::metadata(entry.path()) {
+        Ok(target) => Ok(check(&target.file_type())),
+        Err(e) => {
+            debug!(path = %entry.path().display(), error = %e, "Skipping broken symlink");
+            Ok(false)
+        }
+    }
+}
+
+/// Read a category's sound weights, returning no weights if the file doesn't exist.
+/// Every weight must be a positive number.
+fn load_weights(path: &Path) -> Result<HashMap<String, f64>, ArchiveError> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let parse_error = |message: String| ArchiveError::WeightsParseError {
+        path: path.to_path_buf(),
+        message,
+    };
+
+    let weights: HashMap<String, f64> =
+        toml::from_str(&contents).map_err(|e| parse_error(e.message().to_string()))?;
+
+    if let Some((name, weight)) = weights.iter().find(|(_, w)| !(**w > 0.0 && w.is_finite())) {
+        return Err(parse_error(format!(
+            "weight for '{}' must be a positive number, got {}",
+            name, weight
+        )));
+    }
+
+    Ok(weights)
+}
+
+/// Read a category's `category.toml`, returning defaults if it doesn't exist.
+/// The weight must be a positive number.
+fn load_metadata(path: &Path) -> Result<CategoryMetadata, ArchiveError> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(CategoryMetadata::default()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let parse_error = |message: String| ArchiveError::MetadataParseError {
+        path: path.to_path_buf(),
+        message,
+    };
+
+    let metadata: CategoryMetadata =
+        toml::from_str(&contents).map_err(|e| parse_error(e.message().to_string()))?;
+
+    if !(metadata.weight > 0.0 && metadata.weight.is_finite()) {
+        return Err(parse_error(format!(
+            "weight must be a positive number, got {}",
+            metadata.weight
+        )));
+    }
+
+    Ok(metadata)
+}
+
+/// Replace a malformed weights or metadata file with the defaults, noting it in
+/// `warnings`, so one bad file doesn't stop the whole archive from loading
+fn or_default<T: Default>(
+    result: Result<T, ArchiveError>,
+    warnings: &mut Vec<String>,
+) -> Result<T, ArchiveError> {
+    match result {
+        Err(
+            e @ (ArchiveError::WeightsParseError { .. } | ArchiveError::MetadataParseError { .. }),
+        ) => {
+            warn!(error = %e, "Using defaults");
+            warnings.push(format!("{}; using defaults", e));
+            Ok(T::default())
+        }
+        result => result,
+    }
+}
+
+/// Whether a sound name contains glob metacharacters
+pub fn is_glob(name: &str) -> bool {
+    name.contains(['*', '?', '['])
+}
+
+/// Whether `name` is a single file or directory name: not empty, `.` or `..`,
+/// and without path separators
+pub fn is_plain_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\'])
+}
+
+/// Whether `name` can name a category: plain names joined by `/` (see
+/// `is_plain_name`), so it always stays inside an archive root
+pub fn is_valid_category_name(name: &str) -> bool {
+    name.split('/').all(is_plain_name)
+}
+
+/// Whether a file or directory name is hidden (starts with a dot)
+fn is_hidden(name: &OsStr) -> bool {
+    name.to_str().map(|s| s.starts_with('.')).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// A category holding the given files; nothing is read from disk
+    fn category(files: &[&str]) -> Category {
+        let sounds = files
+            .iter()
+            .map(|file| {
+                let path = PathBuf::from(file);
+                SoundFile {
+                    name: path.file_stem().unwrap().to_string_lossy().into_owned(),
+                    format: AudioFormat::from_extension(
+                        path.extension().unwrap().to_str().unwrap(),
+                    )
+                    .unwrap(),
+                    path,
+                    duration: OnceLock::new(),
+                    size: OnceLock::new(),
                 }
-            }
+            })
+            .collect();
+
+        Category {
+            name: "test".to_string(),
+            display_name: "test".to_string(),
+            parent: None,
+            path: PathBuf::from("test"),
+            sounds,
+            weights: HashMap::new(),
+            metadata: CategoryMetadata::default(),
+        }
+    }
 
-            // Sort sounds alphabetically
-            sounds.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    #[test]
+    fn random_many_with_replacement_returns_count_picks() {
+        let cat = category(&["beep.wav", "chime.wav"]);
+        assert_eq!(cat.random_many(5, true, false).unwrap().len(), 5);
+    }
 
-            categories.push(Category {
-                name: category_name,
-                path: entry_path,
-                sounds,
-            });
+    #[test]
+    fn random_many_without_replacement_never_repeats() {
+        let cat = category(&["beep.wav", "chime.wav", "ding.wav"]);
+        let picked = cat.random_many(3, false, false).unwrap();
+        let names: HashSet<_> = picked.iter().map(|s| &s.name).collect();
+        assert_eq!(names.len(), 3);
+    }
+
+    #[test]
+    fn random_many_without_replacement_needs_enough_sounds() {
+        let cat = category(&["beep.wav", "chime.wav"]);
+        assert!(matches!(
+            cat.random_many(3, false, false),
+            Err(ArchiveError::InsufficientSounds {
+                found: 2,
+                required: 3,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn random_many_wrap_uses_every_sound_before_repeating() {
+        let cat = category(&["beep.wav", "chime.wav"]);
+        let picked = cat.random_many(5, false, true).unwrap();
+        assert_eq!(picked.len(), 5);
+        for batch in picked.chunks(2).filter(|b| b.len() == 2) {
+            assert_ne!(batch[0].name, batch[1].name);
         }
+    }
 
-        // Sort categories alphabetically
-        categories.sort_by(|a, b| a.name.cmp(&b.name));
+    #[test]
+    fn random_many_from_empty_category_fails() {
+        let cat = category(&[]);
+        assert!(matches!(
+            cat.random_many(1, true, false),
+            Err(ArchiveError::EmptyCategory(_))
+        ));
+    }
 
-        Ok(Self { path, categories })
+    #[test]
+    fn random_excluding_skips_names_case_insensitively() {
+        let cat = category(&["beep.wav", "chime.wav"]);
+        for _ in 0..20 {
+            assert_eq!(cat.random_excluding(&["BEEP"], &[]).unwrap().name, "chime");
+        }
     }
 
-    /// Get a category by name (case-insensitive)
-    pub fn category(&self, name: &str) -> Option<&Category> {
-        let name_lower = name.to_lowercase();
-        self.categories.iter().find(|c| c.name == name_lower)
+    #[test]
+    fn random_excluding_format_keeps_same_named_sounds_in_other_formats() {
+        let cat = category(&["beep.wav", "beep.mp3", "chime.wav"]);
+        let mut picked = HashSet::new();
+        for _ in 0..200 {
+            let sound = cat.random_excluding(&[], &[AudioFormat::Mp3]).unwrap();
+            assert_eq!(sound.format, AudioFormat::Wav);
+            picked.insert(sound.path.clone());
+        }
+        assert!(picked.contains(Path::new("beep.wav")));
     }
 
-    /// List all category names
-    pub fn category_names(&self) -> Vec<&str> {
-        self.categories.iter().map(|c| c.name.as_str()).collect()
+    #[test]
+    fn random_excluding_everything_returns_none() {
+        let cat = category(&["beep.wav", "chime.mp3"]);
+        assert!(cat
+            .random_excluding(&["beep"], &[AudioFormat::Mp3])
+            .is_none());
+    }
+
+    #[test]
+    fn random_excluding_honours_weights() {
+        let mut cat = category(&["beep.wav", "chime.wav", "ding.wav"]);
+        cat.weights.insert("chime".to_string(), 0.0);
+        for _ in 0..50 {
+            assert_eq!(cat.random_excluding(&["beep"], &[]).unwrap().name, "ding");
+        }
+    }
+
+    #[test]
+    fn load_skips_missing_secondary_roots() {
+        let primary = std::env::temp_dir().join(format!("cue-archive-test-{}", std::process::id()));
+        fs::create_dir_all(primary.join("alerts")).unwrap();
+        let missing = primary.join("not-mounted");
+        let options = ArchiveLoadOptions::default();
+
+        let archive = SoundArchive::load(&[primary.clone(), missing.clone()], options).unwrap();
+        assert_eq!(archive.root(), primary);
+        assert_eq!(archive.warnings.len(), 1);
+
+        assert!(matches!(
+            SoundArchive::load(&[missing, primary.clone()], options),
+            Err(ArchiveError::NotFound(_))
+        ));
+        assert!(matches!(
+            SoundArchive::load(&[], options),
+            Err(ArchiveError::NoRoots)
+        ));
+
+        fs::remove_dir_all(primary).unwrap();
+    }
+
+    #[test]
+    fn category_names_stay_inside_the_root() {
+        assert!(is_valid_category_name("alerts"));
+        assert!(is_valid_category_name("ambient/rain"));
+        assert!(!is_valid_category_name(""));
+        assert!(!is_valid_category_name("../alerts"));
+        assert!(!is_valid_category_name("ambient/./rain"));
+        assert!(!is_valid_category_name("/etc"));
+        assert!(!is_valid_category_name("ambient\\rain"));
+        assert!(!is_plain_name("ambient/rain"));
+    }
+
+    #[test]
+    fn load_falls_back_to_defaults_for_malformed_category_files() {
+        let root = std::env::temp_dir().join(format!("cue-weights-test-{}", std::process::id()));
+        fs::create_dir_all(root.join("alerts")).unwrap();
+        fs::write(root.join("alerts").join(WEIGHTS_FILE), "beep = \"x").unwrap();
+        fs::write(root.join("alerts").join(METADATA_FILE), "weight = -1").unwrap();
+
+        let archive =
+            SoundArchive::load(std::slice::from_ref(&root), ArchiveLoadOptions::default()).unwrap();
+        let cat = archive.category("alerts").unwrap();
+        assert!(cat.weights.is_empty());
+        assert_eq!(cat.metadata.weight, CategoryMetadata::default().weight);
+        assert_eq!(archive.warnings.len(), 2);
+
+        fs::remove_dir_all(root).unwrap();
     }
 }
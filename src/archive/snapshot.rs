@@ -0,0 +1,317 @@
+use super::{is_hidden, ArchiveError, SoundArchive};
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, BufReader, IsTerminal};
+use std::path::{Component, Path, PathBuf};
+use std::{env, process};
+use tar::{Archive, Builder, Header};
+use tracing::debug;
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Outcome of writing a snapshot
+#[derive(Debug)]
+pub struct SnapshotReport {
+    /// Snapshot directory, or `.tar.gz` file when compressed
+    pub location: PathBuf,
+    pub files: usize,
+    pub total_bytes: u64,
+}
+
+/// Outcome of restoring a snapshot
+#[derive(Debug)]
+pub struct RestoreReport {
+    pub files: usize,
+}
+
+/// Description of a snapshot, stored alongside the files as `manifest.json`
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    created: String,
+    source: PathBuf,
+    categories: usize,
+    sounds: usize,
+    total_bytes: u64,
+    files: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestEntry {
+    /// Path relative to the archive root
+    path: PathBuf,
+    size: u64,
+    sha256: String,
+}
+
+/// Copy the whole archive tree into a new timestamped snapshot under `output_dir`.
+///
+/// The snapshot holds every non-hidden file in the archive plus a manifest with
+/// archive stats and a SHA-256 of each file. With `compress`, the snapshot is
+/// written as `<timestamp>.tar.gz` instead of a directory.
+pub fn create_snapshot(
+    archive: &SoundArchive,
+    output_dir: &Path,
+    compress: bool,
+) -> Result<SnapshotReport, ArchiveError> {
+    let created = Utc::now();
+    let stamp = created.format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut files = Vec::new();
+    collect_files(archive.root(), Path::new(""), &mut Vec::new(), &mut files)?;
+
+    fs::create_dir_all(output_dir)?;
+
+    let location = if compress {
+        output_dir.join(format!("{}.tar.gz", stamp))
+    } else {
+        output_dir.join(&stamp)
+    };
+
+    let mut builder = if compress {
+        let file = File::create(&location)?;
+        Some(Builder::new(GzEncoder::new(file, Compression::default())))
+    } else {
+        fs::create_dir_all(&location)?;
+        None
+    };
+
+    let mut entries = Vec::with_capacity(files.len());
+    for (i, rel) in files.iter().enumerate() {
+        let src = archive.root().join(rel);
+
+        match builder.as_mut() {
+            Some(builder) => builder.append_path_with_name(&src, rel)?,
+            None => {
+                let dest = location.join(rel);
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::copy(&src, &dest)?;
+            }
+        }
+
+        entries.push(ManifestEntry {
+            path: rel.clone(),
+            size: fs::metadata(&src)?.len(),
+            sha256: hash_file(&src)?,
+        });
+        progress("Copying", i + 1, files.len());
+    }
+
+    let manifest = Manifest {
+        created: created.to_rfc3339(),
+        source: archive.root().to_path_buf(),
+        categories: archive.categories.len(),
+        sounds: archive.categories.iter().map(|c| c.sounds.len()).sum(),
+        total_bytes: entries.iter().map(|e| e.size).sum(),
+        files: entries,
+    };
+    let json = serde_json::to_vec_pretty(&manifest).map_err(io::Error::from)?;
+
+    match builder {
+        Some(mut builder) => {
+            let mut header = Header::new_gnu();
+            header.set_size(json.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(created.timestamp().max(0) as u64);
+            header.set_cksum();
+            builder.append_data(&mut header, MANIFEST_FILE, json.as_slice())?;
+            builder.into_inner()?.finish()?;
+        }
+        None => fs::write(location.join(MANIFEST_FILE), json)?,
+    }
+
+    Ok(SnapshotReport {
+        location,
+        files: manifest.files.len(),
+        total_bytes: manifest.total_bytes,
+    })
+}
+
+/// Restore a snapshot directory or `.tar.gz` into the archive rooted at `dest_root`.
+///
+/// Every file is checked against the manifest before anything is copied, so a
+/// corrupt or incomplete snapshot leaves the archive untouched.
+pub fn restore_snapshot(snapshot: &Path, dest_root: &Path) -> Result<RestoreReport, ArchiveError> {
+    if !snapshot.exists() {
+        return Err(ArchiveError::NotFound(snapshot.to_path_buf()));
+    }
+
+    if snapshot.is_dir() {
+        return restore_dir(snapshot, dest_root);
+    }
+
+    let staging = env::temp_dir().join(format!("cue-restore-{}", process::id()));
+    let result = File::open(snapshot)
+        .and_then(|file| Archive::new(GzDecoder::new(file)).unpack(&staging))
+        .map_err(ArchiveError::from)
+        .and_then(|_| restore_dir(&staging, dest_root));
+    let _ = fs::remove_dir_all(&staging);
+
+    result
+}
+
+fn restore_dir(dir: &Path, dest_root: &Path) -> Result<RestoreReport, ArchiveError> {
+    let manifest_path = dir.join(MANIFEST_FILE);
+    let file = File::open(&manifest_path)
+        .map_err(|_| ArchiveError::InvalidSnapshot(format!("missing {}", MANIFEST_FILE)))?;
+    let manifest: Manifest = serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| ArchiveError::InvalidSnapshot(e.to_string()))?;
+
+    let mut corrupt = Vec::new();
+    for (i, entry) in manifest.files.iter().enumerate() {
+        if !is_contained(&entry.path) {
+            corrupt.push(format!("{} (outside the archive)", entry.path.display()));
+            continue;
+        }
+
+        let path = dir.join(&entry.path);
+        if hash_file(&path).ok().as_deref() != Some(entry.sha256.as_str()) {
+            corrupt.push(entry.path.display().to_string());
+        }
+        progress("Verifying", i + 1, manifest.files.len());
+    }
+
+    if !corrupt.is_empty() {
+        return Err(ArchiveError::CorruptSnapshot(corrupt.join(", ")));
+    }
+
+    for (i, entry) in manifest.files.iter().enumerate() {
+        if !is_contained(&entry.path) {
+            return Err(ArchiveError::CorruptSnapshot(
+                entry.path.display().to_string(),
+            ));
+        }
+
+        let dest = dest_root.join(&entry.path);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(dir.join(&entry.path), &dest)?;
+        progress("Restoring", i + 1, manifest.files.len());
+    }
+
+    Ok(RestoreReport {
+        files: manifest.files.len(),
+    })
+}
+
+/// Whether a manifest path stays inside the directory it's joined to: relative,
+/// with no `..`, root, or drive prefix. A crafted manifest could otherwise write
+/// anywhere on restore.
+fn is_contained(path: &Path) -> bool {
+    path.components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// Collect every non-hidden file under `root`, as paths relative to it, in sorted order.
+/// Symlinks are followed, like the archive does; links whose target is missing,
+/// and directories that link back to one being collected, are skipped.
+/// `ancestors` holds the canonical paths of the directories being collected.
+fn collect_files(
+    root: &Path,
+    rel: &Path,
+    ancestors: &mut Vec<PathBuf>,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), ArchiveError> {
+    let dir = root.join(rel);
+    let canonical = fs::canonicalize(&dir)?;
+    if ancestors.contains(&canonical) {
+        debug!(path = %dir.display(), "Skipping symlink cycle");
+        return Ok(());
+    }
+    ancestors.push(canonical);
+
+    let mut entries: Vec<_> = fs::read_dir(&dir)?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(|e| e.file_name());
+
+    for entry in entries {
+        if is_hidden(&entry.file_name()) {
+            continue;
+        }
+
+        let metadata = match fs::metadata(entry.path()) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                debug!(path = %entry.path().display(), error = %e, "Skipping broken symlink");
+                continue;
+            }
+        };
+
+        let path = rel.join(entry.file_name());
+        if metadata.is_dir() {
+            collect_files(root, &path, ancestors, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+
+    ancestors.pop();
+    Ok(())
+}
+
+fn hash_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Show a `label done/total` counter on stderr when it's a terminal
+fn progress(label: &str, done: usize, total: usize) {
+    if !io::stderr().is_terminal() {
+        return;
+    }
+
+    eprint!("\r{} {}/{}", label, done, total);
+    if done == total {
+        eprintln!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_paths_must_stay_inside_the_archive() {
+        assert!(is_contained(Path::new("alerts/beep.wav")));
+        assert!(is_contained(Path::new("./alerts/beep.wav")));
+        assert!(!is_contained(Path::new("../beep.wav")));
+        assert!(!is_contained(Path::new("alerts/../../beep.wav")));
+        assert!(!is_contained(Path::new("/etc/passwd")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn collect_files_follows_symlinked_categories_without_looping() {
+        use std::os::unix::fs::symlink;
+
+        let base = env::temp_dir().join(format!("cue-snapshot-test-{}", process::id()));
+        let root = base.join("sounds");
+        let outside = base.join("elsewhere");
+        fs::create_dir_all(root.join("alerts")).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(root.join("alerts/beep.wav"), b"beep").unwrap();
+        fs::write(outside.join("rain.wav"), b"rain").unwrap();
+        symlink(&outside, root.join("ambient")).unwrap();
+        symlink(&root, root.join("alerts/loop")).unwrap();
+        symlink(base.join("missing"), root.join("broken")).unwrap();
+
+        let mut files = Vec::new();
+        collect_files(&root, Path::new(""), &mut Vec::new(), &mut files).unwrap();
+        assert_eq!(
+            files,
+            [
+                PathBuf::from("alerts/beep.wav"),
+                PathBuf::from("ambient/rain.wav")
+            ]
+        );
+
+        fs::remove_dir_all(base).unwrap();
+    }
+}
@@ -0,0 +1,70 @@
+use super::import::move_file;
+use super::{ArchiveError, Category};
+use crate::playback;
+use rayon::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How much of each file `validate_categories` decodes
+const VALIDATE_LENGTH: Duration = Duration::from_secs(1);
+
+/// A sound that failed to decode
+#[derive(Debug)]
+pub struct ValidationFailure {
+    pub category: String,
+    pub path: PathBuf,
+    pub error: String,
+}
+
+/// Outcome of decoding every sound in a set of categories
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub passed: usize,
+    pub failed: Vec<ValidationFailure>,
+}
+
+/// Decode the first second of every sound in `categories`, in parallel
+pub fn validate_categories(categories: &[&Category]) -> ValidationReport {
+    let sounds: Vec<_> = categories
+        .iter()
+        .flat_map(|cat| cat.sounds.iter().map(move |s| (&cat.name, s)))
+        .collect();
+
+    let failed: Vec<ValidationFailure> = sounds
+        .par_iter()
+        .filter_map(|(category, sound)| {
+            playback::validate(&sound.path, VALIDATE_LENGTH)
+                .err()
+                .map(|e| ValidationFailure {
+                    category: category.to_string(),
+                    path: sound.path.clone(),
+                    error: e.to_string(),
+                })
+        })
+        .collect();
+
+    ValidationReport {
+        passed: sounds.len() - failed.len(),
+        failed,
+    }
+}
+
+/// Move a failed sound to `<dir>/<category>/`, returning where it went.
+/// An existing file there is not overwritten; a numbered name is used instead.
+pub fn quarantine(failure: &ValidationFailure, dir: &Path) -> Result<PathBuf, ArchiveError> {
+    let dest_dir = dir.join(&failure.category);
+    fs::create_dir_all(&dest_dir)?;
+
+    let file_name = failure.path.file_name().unwrap_or_default();
+    let mut dest = dest_dir.join(file_name);
+    for n in 1.. {
+        if !dest.exists() {
+            break;
+        }
+        dest = dest_dir.join(format!("{}.{}", file_name.to_string_lossy(), n));
+    }
+
+    move_file(&failure.path, &dest)?;
+    Ok(dest)
+}
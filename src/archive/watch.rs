@@ -0,0 +1,152 @@
+use super::{is_hidden, ArchiveError, AudioFormat, SoundArchive};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// How long a new file must go without further events before it's considered complete
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Watch `dir` for newly added audio files and call `on_new` for each one.
+///
+/// Files are reported once writes to them have settled for 200ms, and only if
+/// their contents look like a supported audio format. Runs until the watcher
+/// shuts down.
+pub fn watch_new_files(
+    dir: &Path,
+    mut on_new: impl FnMut(&Path, AudioFormat),
+) -> Result<(), ArchiveError> {
+    if !dir.is_dir() {
+        return Err(ArchiveError::NotDirectory(dir.to_path_buf()));
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+    // New files and when we last saw an event for them
+    let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+
+    loop {
+        match rx.recv_timeout(DEBOUNCE / 4) {
+            Ok(event) => {
+                let event = event?;
+                let is_new = matches!(
+                    event.kind,
+                    EventKind::Create(_)
+                        | EventKind::Modify(ModifyKind::Name(RenameMode::To | RenameMode::Both))
+                );
+
+                for path in event.paths {
+                    if is_new || pending.contains_key(&path) {
+                        debug!(path = %path.display(), kind = ?event.kind, "Watch event");
+                        pending.insert(path, Instant::now());
+                    }
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+
+        let settled: Vec<PathBuf> = pending
+            .iter()
+            .filter(|(_, seen)| seen.elapsed() >= DEBOUNCE)
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        for path in settled {
+            pending.remove(&path);
+
+            if path.file_name().is_some_and(is_hidden) || !path.is_file() {
+                continue;
+            }
+
+            match sniff_format(&path) {
+                Some(format) => on_new(&path, format),
+                None => debug!(path = %path.display(), "Ignoring file that isn't recognized audio"),
+            }
+        }
+    }
+}
+
+/// Read a file's leading bytes and detect its audio format
+fn sniff_format(path: &Path) -> Option<AudioFormat> {
+    let mut header = [0u8; 12];
+    let mut file = File::open(path).ok()?;
+    let read = file.read(&mut header).ok()?;
+    AudioFormat::from_magic_bytes(&header[..read])
+}
+
+/// A running archive watcher started by `SoundArchive::watch`. Dropping it stops
+/// watching and waits for any in-progress reload to finish.
+pub struct WatchHandle {
+    watcher: Option<RecommendedWatcher>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        // Dropping the watcher closes the event channel, which ends the thread
+        self.watcher.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl SoundArchive {
+    /// Watch the archive's roots and call `on_change` with a freshly loaded archive
+    /// whenever files are added, removed, or renamed.
+    ///
+    /// Changes are batched until events have settled for 200ms. If reloading
+    /// fails (e.g. a root was removed), a warning is logged and the callback is skipped.
+    pub fn watch(
+        &self,
+        on_change: impl Fn(SoundArchive) + Send + 'static,
+    ) -> Result<WatchHandle, ArchiveError> {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        for root in &self.paths {
+            watcher.watch(root, RecursiveMode::Recursive)?;
+        }
+
+        let paths = self.paths.clone();
+        let options = self.options;
+
+        let thread = thread::spawn(move || {
+            let mut changed_at: Option<Instant> = None;
+
+            loop {
+                match rx.recv_timeout(DEBOUNCE / 4) {
+                    Ok(Ok(event)) if !matches!(event.kind, EventKind::Access(_)) => {
+                        debug!(paths = ?event.paths, kind = ?event.kind, "Archive changed");
+                        changed_at = Some(Instant::now());
+                    }
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => warn!(error = %e, "Archive watch error"),
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                }
+
+                if changed_at.is_some_and(|t| t.elapsed() >= DEBOUNCE) {
+                    changed_at = None;
+                    match SoundArchive::load(&paths, options) {
+                        Ok(archive) => on_change(archive),
+                        Err(e) => warn!(error = %e, "Failed to reload archive"),
+                    }
+                }
+            }
+        });
+
+        Ok(WatchHandle {
+            watcher: Some(watcher),
+            thread: Some(thread),
+        })
+    }
+}
@@ -1,4 +1,12 @@
-use clap::{Parser, Subcommand};
+use crate::archive::AudioFormat;
+use crate::config::EmptyCategoryAction;
+use crate::picker::PickStrategy;
+use crate::playback::{LatencyMode, RawFormat, SPEED_RANGE};
+use crate::upgrade::Channel;
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "cue")]
@@ -7,28 +15,358 @@ use clap::{Parser, Subcommand};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Command,
+
+    /// When to color output
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// Print what would change (files, config, the installed binary) and what
+    /// would play, without doing any of it. Goes before the subcommand.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Print diagnostics to stderr: archive load time, the sound chosen, the
+    /// output device, and the blocklist check. Also logs debug events to the
+    /// `[logging]` file, if one is set. Goes before the subcommand.
+    #[arg(long, conflicts_with = "quiet")]
+    pub verbose: bool,
+
+    /// Print nothing but errors; `list` prints bare names, one per line.
+    /// Goes before the subcommand.
+    #[arg(short, long)]
+    pub quiet: bool,
 }
 
+// Play's many flags make it the largest variant; parsed once, so the size doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 pub enum Command {
     /// Play a random sound from a category
+    #[command(group(ArgGroup::new("multi_play").args(["interleave", "balance_categories"])))]
     Play {
-        /// The category to play from
-        category: String,
+        /// The category to play from (defaults to `default_category` in the config)
+        category: Option<String>,
 
-        /// Volume level 0-100+
-        #[arg(short, long, default_value = "100")]
-        volume: u32,
+        /// Volume level 0-100+ (defaults to `default_volume` in the config, or 100)
+        #[arg(short, long)]
+        volume: Option<u32>,
 
         /// Bypass blocklist check and play anyway
         #[arg(short, long)]
         force: bool,
+
+        /// Print duration statistics for the category to stderr before playing
+        /// (with --dry-run, print them and exit). Probed durations are cached.
+        #[arg(long)]
+        duration_stats: bool,
+
+        /// Print a summary of the category and exit without playing
+        #[arg(long)]
+        category_stats_only: bool,
+
+        /// Output format for diagnostic output
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Shorthand for --format json
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+
+        /// Print the absolute path of the chosen sound and exit without playing it,
+        /// e.g. `mpv $(cue play sfx --print-path)`. The blocklist isn't checked.
+        #[arg(
+            long,
+            conflicts_with_all = ["multi_play", "count", "repeat", "output_format"]
+        )]
+        print_path: bool,
+
+        /// How to choose a sound from the category
+        #[arg(
+            long,
+            visible_alias = "play-order",
+            value_enum,
+            default_value_t = PickStrategy::Random
+        )]
+        pick_strategy: PickStrategy,
+
+        /// Never pick these sounds (names without extension, comma-separated or repeated)
+        #[arg(
+            long,
+            value_name = "SOUNDS",
+            value_delimiter = ',',
+            conflicts_with_all = ["multi_play", "count", "suppress_duplicate_within"]
+        )]
+        exclude: Vec<String>,
+
+        /// Never pick sounds in this format (repeatable), e.g. for a backend that
+        /// can't decode it
+        #[arg(
+            long,
+            value_enum,
+            value_name = "FORMAT",
+            conflicts_with_all = ["multi_play", "count", "suppress_duplicate_within"]
+        )]
+        exclude_format: Vec<AudioFormat>,
+
+        /// Restart the sequential play order from the first sound
+        #[arg(long)]
+        reset: bool,
+
+        /// Stop playback after a random time between MIN and MAX milliseconds
+        #[arg(long, value_name = "MIN_MS:MAX_MS", value_parser = parse_timeout_range)]
+        timeout_random: Option<TimeoutRange>,
+
+        /// What to do if the category has no sounds
+        #[arg(long, value_enum, value_name = "ACTION")]
+        on_empty_category: Option<EmptyCategoryAction>,
+
+        /// Category to play from with `--on-empty-category use-fallback`
+        #[arg(long, value_name = "CATEGORY")]
+        fallback_category: Option<String>,
+
+        /// Allow at most N plays per minute across all invocations
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        rate_limit: Option<u32>,
+
+        /// Seconds to wait for a free rate-limit slot before skipping
+        #[arg(
+            long,
+            value_name = "SECS",
+            default_value = "60",
+            requires = "rate_limit"
+        )]
+        rate_limit_timeout: u64,
+
+        /// Show the selected sound and ask for confirmation before playing
+        #[arg(long)]
+        confirm: bool,
+
+        /// Lower other playing cue sounds to this volume (0-100) while this one plays
+        #[arg(long, value_name = "PERCENT", value_parser = clap::value_parser!(u32).range(0..=100))]
+        mix_volume: Option<u32>,
+
+        /// Allow at most N cue sounds to play at once across all invocations
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        max_concurrent_globally: Option<u32>,
+
+        /// Wait for a free slot instead of skipping when the concurrency limit is reached
+        #[arg(long, requires = "max_concurrent_globally")]
+        wait: bool,
+
+        /// Print category statistics and the selected sound as a JSON line before playing
+        #[arg(long)]
+        category_stats_json: bool,
+
+        /// Alternate random sounds between these categories instead of playing one
+        #[arg(
+            long,
+            num_args = 2..,
+            value_name = "CATEGORY",
+            conflicts_with_all = ["category", "on_empty_category", "fallback_category", "min_sounds_required", "rate_limit"]
+        )]
+        interleave: Option<Vec<String>>,
+
+        /// Pick from whichever category has been played least today
+        #[arg(
+            long,
+            conflicts_with_all = ["category", "on_empty_category", "fallback_category", "min_sounds_required", "rate_limit"]
+        )]
+        balance_categories: bool,
+
+        /// Number of sounds to play one after another. Picks are random with
+        /// replacement unless --no-replace is given. With --interleave or
+        /// --balance-categories the default is one per category, or one when balancing.
+        /// Options that only make sense for a single pick can't be combined with it.
+        #[arg(
+            short = 'n',
+            long,
+            value_name = "N",
+            conflicts_with_all = [
+                "output_format",
+                "pick_strategy",
+                "reset",
+                "suppress_duplicate_within",
+                "rate_limit",
+                "max_concurrent_globally",
+                "on_error_play",
+                "timeout_random",
+                "duration",
+                "confirm",
+                "category_stats_json",
+                "measure_latency",
+            ]
+        )]
+        count: Option<usize>,
+
+        /// With --count, never play the same sound twice; fails if the category has
+        /// fewer than N sounds unless --wrap is given
+        #[arg(long, requires = "count", conflicts_with = "multi_play")]
+        no_replace: bool,
+
+        /// With --no-replace, reshuffle and continue once every sound has played
+        #[arg(long, requires = "no_replace")]
+        wrap: bool,
+
+        /// Play N times, or until interrupted when N is 0 or omitted. With --interleave
+        /// or --balance-categories, repeats the whole round. --fade-in applies to the
+        /// first repetition and --fade-out to the last.
+        #[arg(
+            long = "loop",
+            value_name = "N",
+            num_args = 0..=1,
+            default_missing_value = "0",
+            conflicts_with_all = ["count", "output_format"]
+        )]
+        repeat: Option<u32>,
+
+        /// Stop playing after this many seconds (e.g. to end a --loop 0)
+        #[arg(
+            long,
+            value_name = "SECS",
+            value_parser = parse_secs,
+            conflicts_with_all = ["timeout_random", "multi_play", "output_format"]
+        )]
+        duration: Option<Duration>,
+
+        /// Refuse to play categories with fewer than N sounds (overrides config)
+        #[arg(long, value_name = "N")]
+        min_sounds_required: Option<u32>,
+
+        /// Write the sound to stdout as raw PCM in this format instead of playing it
+        #[arg(long, value_enum, value_name = "FORMAT")]
+        output_format: Option<RawFormat>,
+
+        /// Play --path directly without loading the config or archive. This bypasses
+        /// all validation, including the blocklist; only the file and volume are used.
+        #[arg(
+            long,
+            requires = "path",
+            conflicts_with_all = [
+                "category",
+                "multi_play",
+                "category_stats_only",
+                "category_stats_json",
+                "duration_stats",
+            ]
+        )]
+        skip_archive_load: bool,
+
+        /// Sound file to play with --skip-archive-load
+        #[arg(long, value_name = "FILE", requires = "skip_archive_load")]
+        path: Option<PathBuf>,
+
+        /// Play a random sound from this category if the chosen sound can't be read or decoded
+        #[arg(long, value_name = "CATEGORY")]
+        on_error_play: Option<String>,
+
+        /// Write this process's PID to FILE while playing
+        #[arg(long, value_name = "FILE")]
+        pid_file: Option<PathBuf>,
+
+        /// Take over a --pid-file that names another running cue process
+        #[arg(long, requires = "pid_file")]
+        replace_pid_file: bool,
+
+        /// Avoid sounds from this category played in the last SECS seconds
+        #[arg(
+            long,
+            value_name = "SECS",
+            conflicts_with_all = ["pick_strategy", "reset"]
+        )]
+        suppress_duplicate_within: Option<u64>,
+
+        /// Warn on stderr if the selected sound is a lossy format (MP3/OGG)
+        #[arg(long)]
+        format_warning: bool,
+
+        /// Exit with code N when playback is skipped because of the blocklist
+        #[arg(long, value_name = "N")]
+        report_exit_code_on_blocked: Option<u8>,
+
+        /// Exit with code N when the category has no sounds
+        #[arg(long, value_name = "N")]
+        report_exit_code_on_empty: Option<u8>,
+
+        /// Report time to first sample and total setup time after playback
+        /// (as a JSON line on stdout with --format json)
+        #[arg(long)]
+        measure_latency: bool,
+
+        /// Fail with exit code 2 instead of skipping when a blocklisted app is running
+        #[arg(long, conflicts_with = "force")]
+        require_blocklist_clear: bool,
+
+        /// Output buffer sizing: low latency, balanced (default), or high stability
+        #[arg(long, value_enum)]
+        latency_mode: Option<LatencyMode>,
+
+        /// Ramp the volume up from silence over this many seconds
+        #[arg(long, value_name = "SECS", value_parser = parse_secs, conflicts_with = "output_format")]
+        fade_in: Option<Duration>,
+
+        /// Ramp the volume down to silence over the last this many seconds
+        #[arg(long, value_name = "SECS", value_parser = parse_secs, conflicts_with = "output_format")]
+        fade_out: Option<Duration>,
+
+        /// Play on the first output device whose name contains NAME (see `cue devices`)
+        #[arg(long, value_name = "NAME", conflicts_with = "output_format")]
+        device: Option<String>,
+
+        /// Playback speed from 0.1 to 10.0 (pitch changes with speed)
+        #[arg(
+            long,
+            value_name = "FACTOR",
+            value_parser = parse_speed,
+            default_value_t = 1.0,
+            conflicts_with = "output_format"
+        )]
+        speed: f32,
+
+        /// Show a progress bar on stderr while the sound plays (only on a terminal)
+        #[arg(long, conflicts_with = "output_format")]
+        progress: bool,
     },
 
     /// List available categories or sounds
     List {
         /// Category to list sounds from (omit for all categories)
         category: Option<String>,
+
+        /// Only list sounds matching this glob pattern (e.g. 'bell*')
+        #[arg(requires = "category")]
+        pattern: Option<String>,
+
+        /// Print category directory paths instead of names
+        #[arg(long)]
+        category_path: bool,
+
+        /// Group a category's sounds under format headings
+        #[arg(long, requires = "category", conflicts_with = "category_path")]
+        group_by_format: bool,
+
+        /// Only list sounds whose path starts with this prefix
+        #[arg(long, value_name = "PREFIX", conflicts_with = "category_path")]
+        sounds_path_prefix: Option<PathBuf>,
+
+        /// Show each sound's format and duration
+        #[arg(short, long, conflicts_with_all = ["category_path", "group_by_format"])]
+        verbose: bool,
+
+        /// Keep running and print the listing again whenever the archive changes
+        #[arg(long)]
+        watch: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Shorthand for --format json; errors are also printed as JSON
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+
+        /// Indent JSON output instead of printing one line
+        #[arg(long)]
+        pretty: bool,
     },
 
     /// Play a specific sound file
@@ -36,12 +374,237 @@ pub enum Command {
         /// The category containing the sound
         category: String,
 
-        /// The sound name (without extension)
+        /// The sound name (without extension); glob patterns play every match
         sound: String,
 
-        /// Volume level 0-100+
+        /// Volume level 0-100+ (defaults to `default_volume` in the config, or 100)
+        #[arg(short, long)]
+        volume: Option<u32>,
+
+        /// Print an ASCII waveform of the sound before playing
+        #[arg(long)]
+        waveform: bool,
+
+        /// Print the waveform and skip playback
+        #[arg(long)]
+        waveform_only: bool,
+
+        /// Copy the sound to this file or directory after previewing
+        #[arg(long, value_name = "PATH")]
+        export: Option<PathBuf>,
+
+        /// Skip playback (useful with --export)
+        #[arg(long)]
+        no_play: bool,
+
+        /// Print the absolute path of each matching sound and exit without playing
+        #[arg(long, conflicts_with_all = ["waveform", "waveform_only", "export", "no_play"])]
+        print_path: bool,
+
+        /// With --print-path, print each sound as a JSON object
+        #[arg(long, requires = "print_path")]
+        json: bool,
+
+        /// Play the sound N times (0 repeats until interrupted). --fade-in applies
+        /// to the first repetition and --fade-out to the last.
+        #[arg(long = "loop", value_name = "N")]
+        repeat: Option<u32>,
+
+        /// Stop playing after this many seconds (e.g. to end a --loop 0)
+        #[arg(long, value_name = "SECS", value_parser = parse_secs, conflicts_with = "speed_sweep")]
+        duration: Option<Duration>,
+
+        /// Play on the first output device whose name contains NAME (see `cue devices`)
+        #[arg(long, value_name = "NAME")]
+        device: Option<String>,
+
+        /// Playback speed from 0.1 to 10.0 (pitch changes with speed)
+        #[arg(
+            long,
+            value_name = "FACTOR",
+            value_parser = parse_speed,
+            default_value_t = 1.0,
+            conflicts_with = "speed_sweep"
+        )]
+        speed: f32,
+
+        /// Show a progress bar on stderr while the sound plays (only on a terminal)
+        #[arg(long)]
+        progress: bool,
+
+        /// Play the sound at each speed from START to END in steps of STEP (e.g. 0.5:2:0.25)
+        #[arg(
+            long,
+            value_name = "START:END:STEP",
+            value_parser = parse_speed_sweep,
+            conflicts_with = "repeat"
+        )]
+        speed_sweep: Option<SpeedSweep>,
+
+        /// Wait for Enter between speeds in a --speed-sweep
+        #[arg(long, requires = "speed_sweep")]
+        confirm: bool,
+
+        /// Silence between repetitions, in milliseconds
+        #[arg(long, value_name = "MS", default_value = "0", requires = "repeat")]
+        gap: u64,
+
+        /// Show what --export would copy without copying
+        #[arg(long, requires = "export")]
+        dry_run: bool,
+
+        /// Ramp the volume up from silence over this many seconds
+        #[arg(long, value_name = "SECS", value_parser = parse_secs, conflicts_with = "speed_sweep")]
+        fade_in: Option<Duration>,
+
+        /// Ramp the volume down to silence over the last this many seconds
+        #[arg(long, value_name = "SECS", value_parser = parse_secs, conflicts_with = "speed_sweep")]
+        fade_out: Option<Duration>,
+    },
+
+    /// Find sounds by sound or category name
+    Search {
+        /// Text to look for (case-insensitive)
+        query: String,
+
+        /// Maximum number of results
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+
+        /// Also match names within a small edit distance of the query
+        #[arg(long)]
+        fuzzy: bool,
+
+        /// Play the top result
+        #[arg(long)]
+        play: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Shorthand for --format json
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+    },
+
+    /// Browse categories and preview sounds in an interactive terminal UI
+    Tui {
+        /// Starting volume level 0-100+ (adjust with +/- in the UI)
         #[arg(short, long, default_value = "100")]
         volume: u32,
+
+        /// Audio output device name (see `cue devices`)
+        #[arg(long)]
+        device: Option<String>,
+    },
+
+    /// Print a shell completion script, including the current category names
+    Completions {
+        /// Shell to generate completions for
+        #[arg(long, value_enum)]
+        shell: Shell,
+    },
+
+    /// List audio output device names
+    Devices {
+        /// Mark the system default device with "(default)"
+        #[arg(long)]
+        default: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Shorthand for --format json
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+    },
+
+    /// Show totals for the whole sound archive
+    Stats {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Shorthand for --format json
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+    },
+
+    /// Decode the start of every sound to find broken or mislabeled files
+    Validate {
+        /// Only check this category
+        category: Option<String>,
+
+        /// Move files that fail to ~/.cue/broken/<category>/
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Manage the sound archive
+    Archive {
+        #[command(subcommand)]
+        command: ArchiveCommand,
+    },
+
+    /// Copy audio files into a category, creating it if needed
+    Add {
+        /// Category to add the files to
+        category: String,
+
+        /// Audio files to add
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+
+        /// Move the files instead of copying them
+        #[arg(long = "move")]
+        move_files: bool,
+    },
+
+    /// Delete a sound file from the archive
+    Remove {
+        /// The category containing the sound
+        category: String,
+
+        /// The sound name (case-insensitive, extension optional)
+        sound: String,
+
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Show what would be deleted without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Also delete the category directory if no sounds are left in it
+        #[arg(long)]
+        remove_empty_category: bool,
+    },
+
+    /// Rename a category directory or a sound file
+    Rename {
+        #[command(subcommand)]
+        command: RenameCommand,
+    },
+
+    /// Queue sounds and play them back-to-back
+    Queue {
+        #[command(subcommand)]
+        command: QueueCommand,
+    },
+
+    /// Manage the apps that suppress playback while running
+    Blocklist {
+        #[command(subcommand)]
+        command: BlocklistCommand,
+    },
+
+    /// Manage the config file
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
     },
 
     /// Upgrade to the latest version
@@ -49,5 +612,416 @@ pub enum Command {
         /// Only check for updates, don't install
         #[arg(long)]
         check: bool,
+
+        /// Print the release notes of the available update
+        #[arg(long)]
+        changelog: bool,
+
+        /// Verify the downloaded binary runs before replacing the current one
+        #[arg(long)]
+        self_test: bool,
+
+        /// List available releases instead of upgrading
+        #[arg(long, conflicts_with_all = ["check", "changelog", "version"])]
+        release_list: bool,
+
+        /// Show only the N most recent releases
+        #[arg(long, value_name = "N", requires = "release_list")]
+        limit: Option<usize>,
+
+        /// Install a specific release (e.g. v1.2.3 or 1.2.3) instead of the latest;
+        /// older versions are installed too
+        #[arg(long, visible_alias = "to", value_name = "VERSION")]
+        version: Option<String>,
+
+        /// Release stream to upgrade from (defaults to `upgrade.channel` in the config)
+        #[arg(long, value_enum, conflicts_with_all = ["version", "release_list"])]
+        channel: Option<Channel>,
+
+        /// Delete the previous binary instead of keeping it for `cue rollback`
+        #[arg(long)]
+        no_backup: bool,
+
+        /// Reinstall even if already up to date
+        #[arg(long, conflicts_with_all = ["check", "changelog"])]
+        force: bool,
+
+        /// Fail on the first network error instead of retrying
+        #[arg(long)]
+        no_retry: bool,
+    },
+
+    /// Show the release notes of the latest release
+    Changelog {
+        /// Show the notes of this release (e.g. v1.2.3) instead
+        #[arg(long, value_name = "VERSION")]
+        version: Option<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Shorthand for --format json
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+    },
+
+    /// List binaries kept by previous upgrades, or restore one
+    Rollback {
+        /// Replace the current binary with the kept one for this version
+        #[arg(long, value_name = "VERSION")]
+        to: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ArchiveCommand {
+    /// Remove category directories that contain no sounds
+    Gc {
+        /// Show what would be removed without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Also remove nested empty directories
+        #[arg(long)]
+        all_empty_dirs: bool,
+    },
+
+    /// Import all categories and sounds from another archive
+    Merge {
+        /// Root directory of the archive to import from
+        src_path: PathBuf,
+
+        /// Replace sounds that already exist in the destination
+        #[arg(long)]
+        overwrite: bool,
+
+        /// Show what would be copied without copying anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Back up the primary sounds directory to a timestamped snapshot
+    Snapshot {
+        /// Directory to write the snapshot into
+        output_dir: PathBuf,
+
+        /// Write the snapshot as a .tar.gz instead of a directory
+        #[arg(long)]
+        compress: bool,
+    },
+
+    /// Restore the archive from a snapshot directory or .tar.gz
+    Restore {
+        /// Snapshot created by `cue archive snapshot`
+        snapshot_dir: PathBuf,
+    },
+
+    /// Import the sound files listed in an M3U playlist into a category
+    #[command(name = "import-m3u")]
+    ImportM3u {
+        /// Playlist file; relative entries are resolved against its directory
+        playlist: PathBuf,
+
+        /// Category to import into (created if missing)
+        #[arg(long)]
+        category: String,
+
+        /// Create symlinks to the files instead of copying them
+        #[arg(long)]
+        link: bool,
+    },
+
+    /// Check the archive for problems that would break playback
+    Lint {
+        /// Add read permission to sound files the current user can't read
+        #[arg(long)]
+        fix_permissions: bool,
+
+        /// Show which files --fix-permissions would change
+        #[arg(long, conflicts_with = "fix_permissions")]
+        fix_permissions_dry_run: bool,
+    },
+
+    /// Watch a directory and report (or play) audio files as they are added
+    WatchNew {
+        /// Directory to watch (defaults to the --auto-play category's directory)
+        #[arg(required_unless_present = "auto_play")]
+        path: Option<PathBuf>,
+
+        /// Play each new file as it arrives; without a path, watch this category
+        #[arg(long, value_name = "CATEGORY")]
+        auto_play: Option<String>,
+
+        /// Volume level 0-100+
+        #[arg(short, long, default_value = "100")]
+        volume: u32,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RenameCommand {
+    /// Rename a category directory, moving its alias along with it
+    Category {
+        /// Current category name
+        old: String,
+
+        /// New category name
+        new: String,
+
+        /// Replace an existing category with the new name
+        #[arg(long)]
+        overwrite: bool,
+    },
+
+    /// Rename a sound file, keeping its extension
+    Sound {
+        /// The category containing the sound
+        category: String,
+
+        /// Current sound name (case-insensitive, extension optional)
+        old: String,
+
+        /// New sound name, without extension
+        new: String,
+
+        /// Replace an existing sound with the new name
+        #[arg(long)]
+        overwrite: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum QueueCommand {
+    /// Add a sound to the end of the queue
+    Add {
+        /// Category to take the sound from
+        category: String,
+
+        /// Sound name (default: a random sound from the category)
+        sound: Option<String>,
+
+        /// Volume level 0-100+
+        #[arg(short, long, default_value = "100")]
+        volume: u32,
+    },
+
+    /// Play every queued sound without gaps
+    Play {
+        /// Bypass blocklist check and play anyway
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Remove every sound from the queue
+    Clear,
+
+    /// Show the queued sounds in play order
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum BlocklistCommand {
+    /// Add a process name (or pattern, see `blocklist_mode`) to the blocklist
+    Add { process: String },
+
+    /// Remove an entry from the blocklist
+    Remove { process: String },
+
+    /// Print the blocklist
+    List {
+        /// Also show which entries match a running process
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Remove every entry, so nothing suppresses playback
+    Clear,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Create a config file by answering a few questions
+    Init {
+        /// Replace an existing config file
+        #[arg(long)]
+        overwrite: bool,
+    },
+
+    /// Print the effective configuration, including environment overrides, as TOML
+    Show,
+
+    /// Migrate the config file to the current schema
+    Upgrade {
+        /// Show which migrations would run without changing the file
+        #[arg(long)]
+        dry_run: bool,
     },
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Color when stdout is a terminal and NO_COLOR isn't set
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// Inclusive range of playback timeouts, parsed from "MIN_MS:MAX_MS"
+#[derive(Clone, Copy, Debug)]
+pub struct TimeoutRange {
+    pub min: Duration,
+    pub max: Duration,
+}
+
+fn parse_timeout_range(s: &str) -> Result<TimeoutRange, String> {
+    let (min, max) = s
+        .split_once(':')
+        .ok_or_else(|| "expected MIN_MS:MAX_MS".to_string())?;
+
+    let min: u64 = min
+        .parse()
+        .map_err(|_| format!("invalid minimum: {}", min))?;
+    let max: u64 = max
+        .parse()
+        .map_err(|_| format!("invalid maximum: {}", max))?;
+
+    if min == 0 || max == 0 {
+        return Err("timeouts must be positive".to_string());
+    }
+    if min > max {
+        return Err(format!("minimum ({}) exceeds maximum ({})", min, max));
+    }
+
+    Ok(TimeoutRange {
+        min: Duration::from_millis(min),
+        max: Duration::from_millis(max),
+    })
+}
+
+/// Parse a non-negative number of seconds, allowing fractions (e.g. "0.5")
+fn parse_secs(s: &str) -> Result<Duration, String> {
+    match s.parse::<f64>() {
+        Ok(secs) if secs.is_finite() && secs >= 0.0 => Ok(Duration::from_secs_f64(secs)),
+        _ => Err(format!("expected a non-negative number of seconds: {}", s)),
+    }
+}
+
+fn parse_speed(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(speed) if SPEED_RANGE.contains(&speed) => Ok(speed),
+        _ => Err(format!(
+            "expected a speed from {:.1} to {:.1}: {}",
+            SPEED_RANGE.start(),
+            SPEED_RANGE.end(),
+            s
+        )),
+    }
+}
+
+/// Most speeds a single `--speed-sweep` plays
+const MAX_SWEEP_STEPS: usize = 100;
+
+/// Playback speeds to step through, parsed from "START:END:STEP"
+#[derive(Clone, Copy, Debug)]
+pub struct SpeedSweep {
+    pub start: f32,
+    pub end: f32,
+    pub step: f32,
+}
+
+impl SpeedSweep {
+    /// Speeds from start to end inclusive, counting down if end is below start.
+    /// At most `MAX_SWEEP_STEPS` speeds, each within `SPEED_RANGE`.
+    pub fn speeds(&self) -> Vec<f32> {
+        let count = self.step_count().min(MAX_SWEEP_STEPS) - 1;
+        let step = if self.end < self.start {
+            -self.step
+        } else {
+            self.step
+        };
+
+        (0..=count)
+            .map(|i| (self.start + step * i as f32).clamp(*SPEED_RANGE.start(), *SPEED_RANGE.end()))
+            .collect()
+    }
+
+    /// Number of speeds from start to end inclusive
+    fn step_count(&self) -> usize {
+        ((self.end - self.start).abs() / self.step + 1e-4).floor() as usize + 1
+    }
+}
+
+fn parse_speed_sweep(s: &str) -> Result<SpeedSweep, String> {
+    let parts: Vec<&str> = s.split(':').collect();
+    let [start, end, step] = parts[..] else {
+        return Err("expected START:END:STEP".to_string());
+    };
+
+    let sweep = SpeedSweep {
+        start: parse_speed(start)?,
+        end: parse_speed(end)?,
+        step: match step.parse::<f32>() {
+            Ok(v) if v.is_finite() && v > 0.0 => v,
+            _ => return Err(format!("step must be a positive number: {}", step)),
+        },
+    };
+
+    if sweep.step_count() > MAX_SWEEP_STEPS {
+        return Err(format!(
+            "step {} is too small: a sweep can play at most {} speeds",
+            step, MAX_SWEEP_STEPS
+        ));
+    }
+
+    Ok(sweep)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_speed_sweep_reads_start_end_step() {
+        let sweep = parse_speed_sweep("0.5:2:0.5").unwrap();
+        assert_eq!((sweep.start, sweep.end, sweep.step), (0.5, 2.0, 0.5));
+        assert_eq!(sweep.speeds(), vec![0.5, 1.0, 1.5, 2.0]);
+    }
+
+    #[test]
+    fn parse_speed_sweep_counts_down() {
+        let sweep = parse_speed_sweep("2:1:0.5").unwrap();
+        assert_eq!(sweep.speeds(), vec![2.0, 1.5, 1.0]);
+    }
+
+    #[test]
+    fn parse_speed_sweep_rejects_bad_input() {
+        assert!(parse_speed_sweep("1:2").is_err());
+        assert!(parse_speed_sweep("1:2:0.5:1").is_err());
+        assert!(parse_speed_sweep("0:2:0.5").is_err());
+        assert!(parse_speed_sweep("1:2:-0.5").is_err());
+        assert!(parse_speed_sweep("1:fast:0.5").is_err());
+        assert!(parse_speed_sweep("1:inf:0.5").is_err());
+    }
+
+    #[test]
+    fn parse_speed_sweep_rejects_speeds_outside_the_playable_range() {
+        assert!(parse_speed_sweep("1:20:1").is_err());
+        assert!(parse_speed_sweep("0.05:1:0.05").is_err());
+        assert!(parse_speed_sweep("0.1:10:0.1").is_ok());
+        assert!(parse_speed_sweep("1:2:0.000001").is_err());
+    }
+
+    #[test]
+    fn speed_sweep_stays_inside_the_playable_range() {
+        let speeds = parse_speed_sweep("0.1:10:0.1").unwrap().speeds();
+        assert_eq!(speeds.len(), 100);
+        assert!(speeds.iter().all(|s| SPEED_RANGE.contains(s)));
+    }
+}
@@ -0,0 +1,91 @@
+//! Terminal colors for human-readable output, following `--color` and NO_COLOR.
+
+use crate::archive::AudioFormat;
+use crate::cli::ColorChoice;
+use owo_colors::{AnsiColors, DynColors, OwoColorize, Style};
+use std::collections::BTreeMap;
+use std::env;
+use std::io::{self, IsTerminal};
+use tracing::warn;
+
+/// Styles for each colored part of the output. Every method returns its text
+/// unchanged when colors are off.
+pub struct Palette {
+    enabled: bool,
+    category: Style,
+    count: Style,
+    formats: BTreeMap<AudioFormat, Style>,
+}
+
+impl Palette {
+    /// Build the palette for `choice`, with colors from the config's `[colors]`
+    /// table (component name to color name) replacing the defaults
+    pub fn new(choice: ColorChoice, overrides: &BTreeMap<String, String>) -> Self {
+        let enabled = match choice {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+            }
+        };
+
+        let color = |component: &str, default: AnsiColors| -> DynColors {
+            match overrides.get(component) {
+                Some(name) => name.to_lowercase().parse().unwrap_or_else(|_| {
+                    warn!(component, color = %name, "Unknown color in [colors]; using the default");
+                    DynColors::Ansi(default)
+                }),
+                None => DynColors::Ansi(default),
+            }
+        };
+        let formats = [
+            (AudioFormat::Mp3, AnsiColors::Yellow),
+            (AudioFormat::Wav, AnsiColors::Green),
+            (AudioFormat::Ogg, AnsiColors::Magenta),
+            (AudioFormat::Flac, AnsiColors::Blue),
+        ]
+        .into_iter()
+        .map(|(format, default)| (format, Style::new().color(color(format.name(), default))))
+        .collect();
+
+        Self {
+            enabled,
+            category: Style::new()
+                .bold()
+                .color(color("category", AnsiColors::Cyan)),
+            count: match overrides.get("count") {
+                Some(_) => Style::new().color(color("count", AnsiColors::Default)),
+                None => Style::new().dimmed(),
+            },
+            formats,
+        }
+    }
+
+    /// A category name
+    pub fn category(&self, text: &str) -> String {
+        self.paint(text, self.category)
+    }
+
+    /// A sound count, e.g. "(3 sounds)"
+    pub fn count(&self, text: &str) -> String {
+        self.paint(text, self.count)
+    }
+
+    /// A format label, colored by `format`
+    pub fn format(&self, format: AudioFormat, text: &str) -> String {
+        let style = self
+            .formats
+            .get(&format)
+            .copied()
+            .unwrap_or_else(Style::new);
+        self.paint(text, style)
+    }
+
+    fn paint(&self, text: &str, style: Style) -> String {
+        if self.enabled {
+            text.style(style).to_string()
+        } else {
+            text.to_string()
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! Versioned, in-place upgrades of the config file.
+//!
+//! Each migration takes the raw TOML table from the previous version to the
+//! next. The file records how far it has been migrated in `config_version`,
+//! so running the upgrade again only applies migrations added since.
+
+use toml::Table;
+
+/// Key recording which migrations have been applied
+pub const VERSION_KEY: &str = "config_version";
+
+/// A single schema change, applied to the raw config table
+pub struct Migration {
+    /// Version the config is at after this migration
+    pub version: u32,
+    pub description: &'static str,
+    apply: fn(&mut Table),
+}
+
+/// All migrations, oldest first. Append new ones; never reorder or remove.
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "Remove empty sounds_path so the default location is used",
+    apply: drop_empty_sounds_path,
+}];
+
+/// Version recorded in a config table (0 for files that predate versioning)
+pub fn version_of(table: &Table) -> u32 {
+    table
+        .get(VERSION_KEY)
+        .and_then(|v| v.as_integer())
+        .and_then(|v| u32::try_from(v).ok())
+        .unwrap_or(0)
+}
+
+/// Apply every migration newer than the table's version and stamp the new version.
+/// Returns the migrations that were applied.
+pub fn migrate(table: &mut Table) -> Vec<&'static Migration> {
+    let from = version_of(table);
+    let pending: Vec<&Migration> = MIGRATIONS.iter().filter(|m| m.version > from).collect();
+
+    for migration in &pending {
+        (migration.apply)(table);
+    }
+
+    if let Some(last) = pending.last() {
+        table.insert(VERSION_KEY.to_string(), i64::from(last.version).into());
+    }
+
+    pending
+}
+
+fn drop_empty_sounds_path(table: &mut Table) {
+    let empty = table
+        .get("sounds_path")
+        .and_then(|v| v.as_str())
+        .is_some_and(|s| s.trim().is_empty());
+
+    if empty {
+        table.remove("sounds_path");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn latest() -> u32 {
+        MIGRATIONS.last().unwrap().version
+    }
+
+    #[test]
+    fn migrate_upgrades_an_unversioned_table() {
+        let mut table: Table = toml::from_str("sounds_path = \"  \"\nvolume = 50").unwrap();
+
+        let applied = migrate(&mut table);
+        assert_eq!(applied.len(), MIGRATIONS.len());
+        assert_eq!(version_of(&table), latest());
+        assert!(!table.contains_key("sounds_path"));
+    }
+
+    #[test]
+    fn migrate_is_a_no_op_on_an_already_stamped_table() {
+        let mut table: Table = toml::from_str("sounds_path = \"\"").unwrap();
+        table.insert(VERSION_KEY.to_string(), i64::from(latest()).into());
+        let before = table.clone();
+
+        assert!(migrate(&mut table).is_empty());
+        assert_eq!(table, before);
+    }
+}
@@ -1,8 +1,23 @@
-use serde::Deserialize;
+pub mod migrations;
+
+use crate::archive::{self, ArchiveLoadOptions, SortOrder};
+use crate::http::SecretToken;
+use crate::playback::LatencyMode;
+use crate::suppression::{BlocklistMode, QuietHours, QuietHoursAction};
+use crate::upgrade::Channel;
+use clap::ValueEnum;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::{env, fs};
 use thiserror::Error;
 
+/// Highest volume accepted for `default_volume`
+pub const MAX_DEFAULT_VOLUME: u32 = 100;
+
+/// Cap on the combined volume when a `[volumes]` entry scales a sound
+const MAX_CATEGORY_VOLUME: f32 = 2.0;
+
 #[derive(Debug, Error)]
 pub enum ConfigError {
     #[error("Failed to read config file: {0}")]
@@ -10,15 +25,304 @@ pub enum ConfigError {
 
     #[error("Failed to parse config file: {0}")]
     ParseError(#[from] toml::de::Error),
+
+    #[error("Failed to write config file: {0}")]
+    WriteError(#[from] toml::ser::Error),
+
+    #[error("Config file not found: {0}")]
+    NotFound(PathBuf),
+
+    #[error("Config file already exists: {0} (use --overwrite to replace it)")]
+    AlreadyExists(PathBuf),
+
+    #[error("Failed to edit config file: {0}")]
+    EditError(#[from] toml_edit::TomlError),
+}
+
+/// Answers to the `cue config init` prompts
+#[derive(Debug)]
+pub struct InitOptions {
+    /// Written as entered, so `~/...` stays portable
+    pub sounds_path: String,
+    pub default_volume: u32,
+    pub default_category: Option<String>,
+}
+
+/// Outcome of upgrading the config file to the current schema
+#[derive(Debug)]
+pub struct UpgradeReport {
+    pub path: PathBuf,
+    pub from_version: u32,
+    pub to_version: u32,
+
+    /// Descriptions of the migrations applied, oldest first
+    pub applied: Vec<&'static str>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
-    pub sounds_path: PathBuf,
+    /// Sounds archive directories, given as a single path or an array of paths;
+    /// use `all_sounds_paths()` to read them
+    #[serde(default, deserialize_with = "deserialize_paths")]
+    pub sounds_path: Vec<PathBuf>,
 
     /// List of process names that suppress playback
     #[serde(default = "default_blocklist")]
     pub blocklist: Vec<String>,
+
+    /// Blocklists for individual categories, keyed by full or leaf category name
+    #[serde(default)]
+    pub category_blocklist: BTreeMap<String, Vec<String>>,
+
+    /// Whether a category's blocklist replaces the global one or adds to it
+    #[serde(default)]
+    pub category_blocklist_mode: CategoryBlocklistMode,
+
+    /// How blocklist entries match process names: substring, exact or regex
+    #[serde(default)]
+    pub blocklist_mode: BlocklistMode,
+
+    /// Daily window with no playback, e.g. `{ start = "22:00", end = "07:00" }`
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+
+    /// What playback does during quiet hours: silence, warn or error
+    #[serde(default)]
+    pub quiet_hours_action: QuietHoursAction,
+
+    /// Entries matched against the focused window's title and app name, for
+    /// calls in a browser tab; needs the `window-detection` build feature
+    #[serde(default)]
+    pub window_blocklist: Vec<String>,
+
+    /// Volume level 0-100 for `cue play` and `cue preview` when `--volume` isn't given
+    #[serde(default = "default_volume", deserialize_with = "deserialize_volume")]
+    pub default_volume: u32,
+
+    /// Category `cue play` uses when none is given
+    #[serde(default)]
+    pub default_category: Option<String>,
+
+    /// What `cue play` does when the chosen category has no sounds
+    #[serde(default)]
+    pub empty_category_action: EmptyCategoryAction,
+
+    /// Category the `use-fallback` empty category action plays from
+    #[serde(default)]
+    pub fallback_category: Option<String>,
+
+    /// Minimum number of sounds a category needs before it will play (0 disables)
+    #[serde(default)]
+    pub min_sounds_required: u32,
+
+    /// Warn on stderr when `cue play` picks an MP3 or OGG sound
+    #[serde(default)]
+    pub warn_lossy: bool,
+
+    /// How many directory levels below the sounds path become categories
+    /// (1 = top-level only, 2 = also `parent/child`)
+    #[serde(default = "default_category_depth")]
+    pub category_depth: usize,
+
+    /// Load symlinked category directories and sound files
+    #[serde(default = "default_follow_symlinks")]
+    pub follow_symlinks: bool,
+
+    /// Order of categories and sounds: alphabetical, reverse, random or modified
+    #[serde(default)]
+    pub sort: SortOrder,
+
+    /// Seed for `sort = "random"`; a new order each run if unset
+    #[serde(default)]
+    pub sort_seed: Option<u64>,
+
+    /// Output device to play on, matched by name substring; the system default if unset
+    #[serde(default)]
+    pub device: Option<String>,
+
+    /// Preferred output buffer sizing for playback
+    #[serde(default)]
+    pub latency_mode: LatencyMode,
+
+    /// Display names for categories, keyed by directory name
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+
+    /// Base volume percentage per category, keyed by full or leaf category name
+    #[serde(default)]
+    pub volumes: BTreeMap<String, u32>,
+
+    /// Colors for `cue list` output: `category`, `count` or a format name
+    /// (`mp3`, `wav`, ...) mapped to a color name such as "bright blue"
+    #[serde(default)]
+    pub colors: BTreeMap<String, String>,
+
+    /// Structured log output settings
+    #[serde(default)]
+    pub logging: LoggingConfig,
+
+    /// Proxy URL for network requests, overriding https_proxy/http_proxy
+    #[serde(default)]
+    pub proxy: Option<String>,
+
+    /// How many times to retry a failed network request (0 disables retries)
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+
+    /// GitHub token for upgrade requests; GITHUB_TOKEN takes precedence
+    #[serde(default)]
+    pub github_token: Option<SecretToken>,
+
+    /// Self-upgrade settings
+    #[serde(default)]
+    pub upgrade: UpgradeConfig,
+}
+
+/// How a `[category_blocklist]` entry combines with the global blocklist
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CategoryBlocklistMode {
+    /// The category's list is used instead of the global one, so an empty
+    /// list lets the category always play
+    #[default]
+    Override,
+
+    /// The category's list is checked as well as the global one
+    Extend,
+}
+
+/// Behavior when playing from a category that has no sounds
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum EmptyCategoryAction {
+    /// Fail with an error (exit code 1)
+    #[default]
+    Error,
+
+    /// Exit successfully without output
+    Skip,
+
+    /// Exit successfully with a warning on stderr
+    Warn,
+
+    /// Play from the fallback category instead
+    UseFallback,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LoggingConfig {
+    /// File to append log events to (logging is disabled when unset)
+    pub file: Option<PathBuf>,
+
+    /// Minimum level: trace, debug, info, warn, or error
+    #[serde(default = "default_log_level")]
+    pub level: String,
+
+    /// Output format: text or json
+    #[serde(default = "default_log_format")]
+    pub format: String,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            file: None,
+            level: default_log_level(),
+            format: default_log_format(),
+        }
+    }
+}
+
+/// `[upgrade]` section of the config file
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpgradeConfig {
+    /// Keep the previous binary in the backups directory for `cue rollback`
+    #[serde(default = "default_keep_backup")]
+    pub keep_backup: bool,
+
+    /// How many previous binaries to keep; the oldest versions are pruned on
+    /// upgrade. 0 keeps them all.
+    #[serde(default = "default_max_backups")]
+    pub max_backups: usize,
+
+    /// Release stream `cue upgrade` follows unless `--channel` is given
+    #[serde(default)]
+    pub channel: Channel,
+}
+
+impl Default for UpgradeConfig {
+    fn default() -> Self {
+        Self {
+            keep_backup: default_keep_backup(),
+            max_backups: default_max_backups(),
+            channel: Channel::default(),
+        }
+    }
+}
+
+/// Accept `sounds_path` as either a single path or an array of paths
+fn deserialize_paths<'de, D>(deserializer: D) -> Result<Vec<PathBuf>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(PathBuf),
+        Many(Vec<PathBuf>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(path) => vec![path],
+        OneOrMany::Many(paths) => paths,
+    })
+}
+
+/// Accept `default_volume` only within 0-100; louder levels must be asked for with --volume
+fn deserialize_volume<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let volume = u32::deserialize(deserializer)?;
+    if volume > MAX_DEFAULT_VOLUME {
+        return Err(serde::de::Error::custom(format!(
+            "default_volume must be between 0 and {}, got {}",
+            MAX_DEFAULT_VOLUME, volume
+        )));
+    }
+    Ok(volume)
+}
+
+fn default_follow_symlinks() -> bool {
+    true
+}
+
+fn default_category_depth() -> usize {
+    archive::DEFAULT_CATEGORY_DEPTH
+}
+
+fn default_volume() -> u32 {
+    100
+}
+
+fn default_keep_backup() -> bool {
+    true
+}
+
+fn default_max_backups() -> usize {
+    3
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+fn default_log_format() -> String {
+    "text".to_string()
 }
 
 fn default_blocklist() -> Vec<String> {
@@ -36,8 +340,34 @@ fn default_blocklist() -> Vec<String> {
 impl Default for Config {
     fn default() -> Self {
         Self {
-            sounds_path: dirs_home().join(".cue").join("sounds"),
+            sounds_path: Vec::new(),
             blocklist: default_blocklist(),
+            category_blocklist: BTreeMap::new(),
+            category_blocklist_mode: CategoryBlocklistMode::default(),
+            blocklist_mode: BlocklistMode::default(),
+            quiet_hours: None,
+            quiet_hours_action: QuietHoursAction::default(),
+            window_blocklist: Vec::new(),
+            default_volume: default_volume(),
+            default_category: None,
+            empty_category_action: EmptyCategoryAction::default(),
+            fallback_category: None,
+            min_sounds_required: 0,
+            warn_lossy: false,
+            category_depth: default_category_depth(),
+            follow_symlinks: default_follow_symlinks(),
+            sort: SortOrder::default(),
+            sort_seed: None,
+            device: None,
+            latency_mode: LatencyMode::default(),
+            aliases: BTreeMap::new(),
+            volumes: BTreeMap::new(),
+            colors: BTreeMap::new(),
+            logging: LoggingConfig::default(),
+            proxy: None,
+            max_retries: default_max_retries(),
+            github_token: None,
+            upgrade: UpgradeConfig::default(),
         }
     }
 }
@@ -45,15 +375,33 @@ impl Default for Config {
 impl Config {
     /// Load configuration with precedence:
     /// 1. CUE_SOUNDS_PATH environment variable
-    /// 2. Config file (~/.config/cue/config.toml)
-    /// 3. Default (~/.cue/sounds)
+    /// 2. Config file at CUE_CONFIG_PATH, if set
+    /// 3. Config file (~/.config/cue/config.toml)
+    /// 4. Default (see `default_sounds_path`)
+    ///
+    /// CUE_UPGRADE_NO_BACKUP=1 turns off `upgrade.keep_backup` in every case.
     pub fn load() -> Result<Self, ConfigError> {
+        let mut config = Self::load_base()?;
+
+        if env::var("CUE_UPGRADE_NO_BACKUP").is_ok_and(|v| v == "1") {
+            config.upgrade.keep_backup = false;
+        }
+
+        Ok(config)
+    }
+
+    fn load_base() -> Result<Self, ConfigError> {
         // Check environment variable first
-        if let Ok(path) = env::var("CUE_SOUNDS_PATH") {
-            if !path.is_empty() {
+        if let Some(paths) = env::var_os("CUE_SOUNDS_PATH") {
+            let sounds_path: Vec<PathBuf> = env::split_paths(&paths)
+                .filter(|p| !p.as_os_str().is_empty())
+                .map(|p| expand_tilde(p.to_string_lossy().as_ref()))
+                .collect();
+
+            if !sounds_path.is_empty() {
                 return Ok(Self {
-                    sounds_path: expand_tilde(&path),
-                    blocklist: default_blocklist(),
+                    sounds_path,
+                    ..Self::default()
                 });
             }
         }
@@ -63,7 +411,15 @@ impl Config {
         if config_path.exists() {
             let contents = fs::read_to_string(&config_path)?;
             let mut config: Config = toml::from_str(&contents)?;
-            config.sounds_path = expand_tilde(config.sounds_path.to_string_lossy().as_ref());
+            config.sounds_path = config
+                .sounds_path
+                .iter()
+                .filter(|p| !p.as_os_str().is_empty())
+                .map(|p| expand_tilde(p.to_string_lossy().as_ref()))
+                .collect();
+            if let Some(file) = &config.logging.file {
+                config.logging.file = Some(expand_tilde(file.to_string_lossy().as_ref()));
+            }
             return Ok(config);
         }
 
@@ -71,8 +427,248 @@ impl Config {
         Ok(Self::default())
     }
 
-    /// Get the config file path
+    /// The blocklist that applies when playing `category` (the global one for
+    /// `None`), matched by full or leaf name like `volume_for`
+    pub fn blocklist_for(&self, category: Option<&str>) -> Vec<String> {
+        let Some(category) = category else {
+            return self.blocklist.clone();
+        };
+
+        let leaf = category.rsplit('/').next().unwrap_or(category);
+        let entry = self
+            .category_blocklist
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(category))
+            .or_else(|| {
+                self.category_blocklist
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(leaf))
+            });
+
+        match (entry, self.category_blocklist_mode) {
+            (None, _) => self.blocklist.clone(),
+            (Some((_, list)), CategoryBlocklistMode::Override) => list.clone(),
+            (Some((_, list)), CategoryBlocklistMode::Extend) => {
+                self.blocklist.iter().chain(list).cloned().collect()
+            }
+        }
+    }
+
+    /// How to load the archive: `category_depth` levels, following symlinks
+    /// unless `follow_symlinks` is off, in `sort` order
+    pub fn archive_options(&self) -> ArchiveLoadOptions {
+        ArchiveLoadOptions {
+            depth: self.category_depth,
+            follow_symlinks: self.follow_symlinks,
+            sort: self.sort,
+            seed: self.sort_seed.unwrap_or_else(archive::session_seed),
+        }
+    }
+
+    /// Playback volume (1.0 = 100%) for a sound from `category` at `volume` percent.
+    /// A `[volumes]` entry for the category, by full or leaf name, is multiplied
+    /// in and the result capped at 200%.
+    pub fn volume_for(&self, category: &str, volume: u32) -> f32 {
+        let volume = volume as f32 / 100.0;
+        let leaf = category.rsplit('/').next().unwrap_or(category);
+        let base = self
+            .volumes
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(category))
+            .or_else(|| {
+                self.volumes
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(leaf))
+            });
+
+        match base {
+            Some((_, base)) => (*base as f32 / 100.0 * volume).min(MAX_CATEGORY_VOLUME),
+            None => volume,
+        }
+    }
+
+    /// The primary sounds archive directory (the first configured one), falling
+    /// back to `default_sounds_path`. Commands that write to the archive use this one.
+    pub fn sounds_path_or_default(&self) -> PathBuf {
+        self.sounds_path
+            .first()
+            .cloned()
+            .unwrap_or_else(Self::default_sounds_path)
+    }
+
+    /// Where sounds live when `sounds_path` isn't set: the platform data directory,
+    /// unless sounds are still in the old ~/.cue/sounds (see `legacy_sounds_path`)
+    pub fn default_sounds_path() -> PathBuf {
+        Self::legacy_sounds_path().unwrap_or_else(Self::data_sounds_path)
+    }
+
+    /// `sounds` in the platform data directory (see `data_dir`)
+    pub fn data_sounds_path() -> PathBuf {
+        Self::data_dir().join("sounds")
+    }
+
+    /// The platform data directory: $XDG_DATA_HOME/cue (or ~/.local/share/cue)
+    /// on Linux, ~/Library/Application Support/cue on macOS
+    pub fn data_dir() -> PathBuf {
+        directories::ProjectDirs::from("", "", "cue")
+            .map(|dirs| dirs.data_dir().to_path_buf())
+            .unwrap_or_else(|| dirs_home().join(".local").join("share").join("cue"))
+    }
+
+    /// The pre-XDG default ~/.cue/sounds, while it's still the one in use:
+    /// it exists and the data directory location doesn't yet
+    pub fn legacy_sounds_path() -> Option<PathBuf> {
+        let legacy = dirs_home().join(".cue").join("sounds");
+        (legacy.is_dir() && !Self::data_sounds_path().exists()).then_some(legacy)
+    }
+
+    /// Every configured sounds archive directory, or the default one if none is set
+    pub fn all_sounds_paths(&self) -> Vec<PathBuf> {
+        if self.sounds_path.is_empty() {
+            vec![self.sounds_path_or_default()]
+        } else {
+            self.sounds_path.clone()
+        }
+    }
+
+    /// Migrate the config file to the current schema, keeping the original as
+    /// `config.toml.bak`. With `dry_run`, reports the migrations without writing.
+    pub fn upgrade_file(dry_run: bool) -> Result<UpgradeReport, ConfigError> {
+        let path = Self::config_path();
+        if !path.exists() {
+            return Err(ConfigError::NotFound(path));
+        }
+
+        let contents = fs::read_to_string(&path)?;
+        let mut table: toml::Table = toml::from_str(&contents)?;
+
+        let from_version = migrations::version_of(&table);
+        let applied = migrations::migrate(&mut table);
+
+        if !dry_run && !applied.is_empty() {
+            fs::write(path.with_extension("toml.bak"), &contents)?;
+            fs::write(&path, toml::to_string_pretty(&table)?)?;
+        }
+
+        Ok(UpgradeReport {
+            path,
+            from_version,
+            to_version: migrations::version_of(&table),
+            applied: applied.iter().map(|m| m.description).collect(),
+        })
+    }
+
+    /// Write a new config file from `options`, creating its directory.
+    /// An existing file is only replaced with `overwrite`. Returns the path written.
+    pub fn init_file(options: &InitOptions, overwrite: bool) -> Result<PathBuf, ConfigError> {
+        let path = Self::config_path();
+        if path.exists() && !overwrite {
+            return Err(ConfigError::AlreadyExists(path));
+        }
+
+        let mut table = toml::Table::new();
+        table.insert(
+            "sounds_path".to_string(),
+            options.sounds_path.clone().into(),
+        );
+        table.insert(
+            "default_volume".to_string(),
+            i64::from(options.default_volume).into(),
+        );
+        if let Some(category) = &options.default_category {
+            table.insert("default_category".to_string(), category.clone().into());
+        }
+
+        // Stamp the current schema version so `cue config upgrade` has nothing to do
+        migrations::migrate(&mut table);
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, toml::to_string_pretty(&table)?)?;
+
+        Ok(path)
+    }
+
+    /// Change the config file's `blocklist` in place with `edit`, keeping the
+    /// rest of the file's comments and formatting. A file without a blocklist
+    /// starts from the default one, and a missing file is created.
+    pub fn edit_blocklist(edit: impl FnOnce(&mut toml_edit::Array)) -> Result<(), ConfigError> {
+        let path = Self::config_path();
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let mut doc: toml_edit::DocumentMut = contents.parse()?;
+
+        if !doc.get("blocklist").is_some_and(|v| v.is_array()) {
+            doc["blocklist"] = toml_edit::value(
+                default_blocklist()
+                    .into_iter()
+                    .collect::<toml_edit::Array>(),
+            );
+        }
+        if let Some(blocklist) = doc["blocklist"].as_array_mut() {
+            edit(blocklist);
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, doc.to_string())?;
+
+        Ok(())
+    }
+
+    /// Re-key the alias for category `old` (given as its full or leaf name) to
+    /// `new` in the config file, so a renamed category keeps its display name.
+    /// Returns whether an alias was moved; without a config file nothing changes.
+    pub fn rename_alias(old: &str, new: &str) -> Result<bool, ConfigError> {
+        let path = Self::config_path();
+        if !path.exists() {
+            return Ok(false);
+        }
+
+        // Edited in place, like `edit_blocklist`, so comments survive
+        let mut doc: toml_edit::DocumentMut = fs::read_to_string(&path)?.parse()?;
+        let Some(aliases) = doc.get_mut("aliases").and_then(|v| v.as_table_like_mut()) else {
+            return Ok(false);
+        };
+
+        let old_leaf = old.rsplit('/').next().unwrap_or(old);
+        let find = |name: &str| {
+            aliases
+                .iter()
+                .map(|(key, _)| key)
+                .find(|key| key.eq_ignore_ascii_case(name))
+                .map(str::to_string)
+        };
+        let Some(key) = find(old).or_else(|| find(old_leaf)) else {
+            return Ok(false);
+        };
+
+        // The key's decor holds any comment above it, which moves with the alias
+        let decor = aliases.key(&key).map(|k| k.leaf_decor().clone());
+        if let Some(alias) = aliases.remove(&key) {
+            let new = new.to_lowercase();
+            aliases.insert(&new, alias);
+            if let (Some(decor), Some(mut key)) = (decor, aliases.key_mut(&new)) {
+                *key.leaf_decor_mut() = decor;
+            }
+        }
+        fs::write(&path, doc.to_string())?;
+
+        Ok(true)
+    }
+
+    /// Get the config file path: CUE_CONFIG_PATH if set, otherwise the
+    /// platform config directory
     pub fn config_path() -> PathBuf {
+        if let Some(path) = env::var_os("CUE_CONFIG_PATH").filter(|p| !p.is_empty()) {
+            return expand_tilde(path.to_string_lossy().as_ref());
+        }
+
         directories::ProjectDirs::from("", "", "cue")
             .map(|dirs| dirs.config_dir().to_path_buf())
             .unwrap_or_else(|| dirs_home().join(".config").join("cue"))
@@ -80,18 +676,78 @@ impl Config {
     }
 }
 
-fn dirs_home() -> PathBuf {
+pub fn dirs_home() -> PathBuf {
     directories::BaseDirs::new()
         .map(|d| d.home_dir().to_path_buf())
         .unwrap_or_else(|| PathBuf::from("/"))
 }
 
 fn expand_tilde(path: &str) -> PathBuf {
-    if path.starts_with("~/") {
-        dirs_home().join(&path[2..])
+    if let Some(rest) = path.strip_prefix("~/") {
+        dirs_home().join(rest)
     } else if path == "~" {
         dirs_home()
     } else {
         PathBuf::from(path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn config_with_category_blocklist(mode: CategoryBlocklistMode) -> Config {
+        let mut config = Config {
+            blocklist: strings(&["zoom"]),
+            category_blocklist_mode: mode,
+            ..Config::default()
+        };
+        config
+            .category_blocklist
+            .insert("alerts".to_string(), strings(&["obs"]));
+        config
+    }
+
+    #[test]
+    fn blocklist_for_falls_back_to_the_global_list() {
+        let config = config_with_category_blocklist(CategoryBlocklistMode::Override);
+        assert_eq!(config.blocklist_for(None), strings(&["zoom"]));
+        assert_eq!(config.blocklist_for(Some("ambient")), strings(&["zoom"]));
+    }
+
+    #[test]
+    fn blocklist_for_overrides_or_extends_by_full_or_leaf_name() {
+        let config = config_with_category_blocklist(CategoryBlocklistMode::Override);
+        assert_eq!(config.blocklist_for(Some("Alerts")), strings(&["obs"]));
+        assert_eq!(config.blocklist_for(Some("work/alerts")), strings(&["obs"]));
+
+        let config = config_with_category_blocklist(CategoryBlocklistMode::Extend);
+        assert_eq!(
+            config.blocklist_for(Some("alerts")),
+            strings(&["zoom", "obs"])
+        );
+    }
+
+    #[test]
+    fn volume_for_scales_by_the_category_volume() {
+        let mut config = Config::default();
+        config.volumes.insert("alerts".to_string(), 50);
+        config.volumes.insert("work/alerts".to_string(), 80);
+
+        assert_eq!(config.volume_for("ambient", 60), 0.6);
+        assert_eq!(config.volume_for("alerts", 100), 0.5);
+        assert_eq!(config.volume_for("home/alerts", 100), 0.5);
+        assert_eq!(config.volume_for("work/alerts", 100), 0.8);
+    }
+
+    #[test]
+    fn volume_for_caps_the_result() {
+        let mut config = Config::default();
+        config.volumes.insert("alerts".to_string(), 200);
+        assert_eq!(config.volume_for("alerts", 200), MAX_CATEGORY_VOLUME);
+    }
+}
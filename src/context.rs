@@ -0,0 +1,64 @@
+//! Settings from the top-level command line that apply to whichever command runs.
+
+use crate::playback::{LatencyMode, PlaybackError, Player};
+use std::fmt::Display;
+
+/// Passed down from `run()` so every command honours the global flags
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Context {
+    /// Print what would change instead of touching files, the binary, or the speakers
+    pub dry_run: bool,
+
+    /// Print diagnostics (load times, the chosen sound, the output device) to stderr
+    pub verbose: bool,
+
+    /// Print only errors and the bare results scripts need
+    pub quiet: bool,
+}
+
+impl Context {
+    /// In a dry run, print `Would <verb>: <detail>` and return `true` so the
+    /// caller skips the action; otherwise return `false` and print nothing
+    pub fn skip(&self, verb: &str, detail: impl Display) -> bool {
+        if self.dry_run {
+            println!("Would {}: {}", verb, detail);
+        }
+        self.dry_run
+    }
+
+    /// Print a diagnostic to stderr with `--verbose`
+    pub fn detail(&self, message: impl Display) {
+        if self.verbose {
+            eprintln!("{}", message);
+        }
+    }
+
+    /// Print a status message to stdout unless `--quiet` is set
+    pub fn say(&self, message: impl Display) {
+        if !self.quiet {
+            println!("{}", message);
+        }
+    }
+
+    /// Print a warning to stderr unless `--quiet` is set
+    pub fn warn(&self, message: impl Display) {
+        if !self.quiet {
+            eprintln!("Warning: {}", message);
+        }
+    }
+
+    /// Open the output device, or in a dry run a player that only prints what
+    /// it would play (no device is needed)
+    pub fn player(&self, device: Option<&str>, mode: LatencyMode) -> Result<Player, PlaybackError> {
+        if self.dry_run {
+            return Ok(Player::dry_run());
+        }
+
+        let player = Player::open(device, mode)?;
+        self.detail(format_args!(
+            "Output device: {}",
+            player.device_name().unwrap_or("unknown")
+        ));
+        Ok(player)
+    }
+}
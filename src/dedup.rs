@@ -0,0 +1,126 @@
+//! Per-category memory of when each sound last played, so a sound isn't
+//! repeated within a time window.
+
+use crate::archive::{Category, SoundFile};
+use crate::state;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Last-played times for one category, stored in ~/.cue/dedup_<hash>.json
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DedupCache {
+    /// Unix timestamp (seconds) of each sound's most recent play, by sound name
+    last_played: HashMap<String, u64>,
+
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl DedupCache {
+    /// Load the cache for a category, starting empty if none exists
+    pub fn load(category: &str) -> Self {
+        let path = Self::path(category);
+        Self {
+            path: path.clone(),
+            ..state::load_json(&path)
+        }
+    }
+
+    /// Pick a random sound not played within `window`. If every sound played
+    /// within it, fall back to the one played longest ago.
+    pub fn pick<'a>(&self, cat: &'a Category, window: Duration) -> Option<&'a SoundFile> {
+        let now = now_secs();
+        let last = |s: &SoundFile| self.last_played.get(&s.name).copied();
+
+        let eligible: Vec<&SoundFile> = cat
+            .sounds
+            .iter()
+            .filter(|s| last(s).is_none_or(|t| now.saturating_sub(t) >= window.as_secs()))
+            .collect();
+
+        match eligible.choose(&mut rand::thread_rng()) {
+            Some(sound) => Some(*sound),
+            None => cat.sounds.iter().min_by_key(|s| last(s)),
+        }
+    }
+
+    /// Record that a sound just played, merging it into the saved cache under
+    /// its lock so concurrent plays keep each other's entries
+    pub fn record(&mut self, sound: &SoundFile) -> io::Result<()> {
+        let now = now_secs();
+        self.last_played.insert(sound.name.clone(), now);
+        state::update_json(&self.path, |saved: &mut Self| {
+            saved.last_played.insert(sound.name.clone(), now);
+        })
+    }
+
+    fn path(category: &str) -> PathBuf {
+        state::state_dir().join(format!("dedup_{:016x}.json", state::stable_hash(category)))
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::{ArchiveLoadOptions, SoundArchive};
+    use std::fs;
+
+    fn scratch(name: &str, files: &[&str]) -> (PathBuf, SoundArchive) {
+        let root = std::env::temp_dir().join(format!("cue-dedup-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("alerts")).unwrap();
+        for file in files {
+            fs::write(root.join("alerts").join(file), b"").unwrap();
+        }
+
+        let archive =
+            SoundArchive::load(std::slice::from_ref(&root), ArchiveLoadOptions::default()).unwrap();
+        (root, archive)
+    }
+
+    fn cache(plays: &[(&str, u64)]) -> DedupCache {
+        DedupCache {
+            last_played: plays.iter().map(|(n, t)| (n.to_string(), *t)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn pick_skips_sounds_played_within_the_window() {
+        let (root, archive) = scratch("window", &["beep.wav", "bell.wav", "ding.wav"]);
+        let cat = archive.category("alerts").unwrap();
+        let now = now_secs();
+        let cache = cache(&[("beep", now), ("bell", now - 30), ("ding", now - 600)]);
+
+        for _ in 0..20 {
+            let sound = cache.pick(cat, Duration::from_secs(60)).unwrap();
+            assert_eq!(sound.name, "ding");
+        }
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn pick_falls_back_to_the_oldest_play() {
+        let (root, archive) = scratch("fallback", &["beep.wav", "bell.wav", "ding.wav"]);
+        let cat = archive.category("alerts").unwrap();
+        let now = now_secs();
+        let cache = cache(&[("beep", now - 10), ("bell", now - 50), ("ding", now - 5)]);
+
+        let sound = cache.pick(cat, Duration::from_secs(60)).unwrap();
+        assert_eq!(sound.name, "bell");
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
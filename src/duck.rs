@@ -0,0 +1,90 @@
+//! Ducking: temporarily lowering the volume of other running `cue` processes.
+//!
+//! A process ducks others by writing a request file to ~/.cue/duck/<pid>.json
+//! and removing it when done. Playback started with `follow_ducking` (`cue play`
+//! without `--mix-volume` of its own) polls that directory and scales its volume
+//! by the lowest level requested by another live process; nothing else polls.
+
+use crate::state;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process;
+use sysinfo::{Pid, System};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DuckRequest {
+    cmd: String,
+
+    /// Volume percentage other processes should drop to
+    volume: u32,
+}
+
+/// Active duck request; other processes are restored when this is dropped
+pub struct DuckGuard {
+    path: PathBuf,
+}
+
+impl DuckGuard {
+    /// Ask other cue processes to reduce their volume to `volume` percent
+    pub fn engage(volume: u32) -> io::Result<Self> {
+        let path = duck_dir().join(format!("{}.json", process::id()));
+        let request = DuckRequest {
+            cmd: "duck".to_string(),
+            volume,
+        };
+
+        state::save_json(&path, &request)?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for DuckGuard {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Volume multiplier requested by other live processes (1.0 when nobody is ducking)
+pub fn level() -> f32 {
+    let Ok(entries) = fs::read_dir(duck_dir()) else {
+        return 1.0;
+    };
+
+    let own_pid = process::id();
+    let mut system: Option<System> = None;
+    let mut level: f32 = 1.0;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(pid) = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.parse::<u32>().ok())
+        else {
+            continue;
+        };
+
+        if pid == own_pid {
+            continue;
+        }
+
+        // Ignore requests left behind by processes that were killed
+        let system = system.get_or_insert_with(System::new);
+        if !system.refresh_process(Pid::from_u32(pid)) {
+            let _ = fs::remove_file(&path);
+            continue;
+        }
+
+        if let Some(request) = state::load_json::<Option<DuckRequest>>(&path) {
+            level = level.min(request.volume as f32 / 100.0);
+        }
+    }
+
+    level
+}
+
+fn duck_dir() -> PathBuf {
+    state::state_dir().join("duck")
+}
@@ -0,0 +1,87 @@
+//! Sound durations remembered between runs, so `play --duration-stats` on a
+//! large category doesn't decode every file each time.
+
+use crate::archive::Category;
+use crate::state;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Probed durations by sound path, stored in ~/.cue/durations.json
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DurationCache {
+    entries: HashMap<String, Entry>,
+
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+/// A cached duration, valid while the file's size and modification time match
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Entry {
+    stamp: Stamp,
+    duration: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Stamp {
+    size: u64,
+    modified_secs: u64,
+}
+
+impl DurationCache {
+    /// Load the cache, starting empty if none exists
+    pub fn load() -> Self {
+        let path = state::state_dir().join("durations.json");
+        Self {
+            path: path.clone(),
+            ..state::load_json(&path)
+        }
+    }
+
+    /// Duration of every sound in a category, in order. Sounds that aren't
+    /// cached, or changed since they were, are probed in parallel.
+    pub fn durations(&mut self, cat: &Category) -> Vec<Option<Duration>> {
+        let probed: Vec<(String, Option<Stamp>, Option<Duration>)> = cat
+            .sounds
+            .par_iter()
+            .map(|sound| {
+                let key = sound.path.to_string_lossy().into_owned();
+                let stamp = stamp(&sound.path);
+                let duration = match (stamp, self.entries.get(&key)) {
+                    (Some(stamp), Some(entry)) if entry.stamp == stamp => entry.duration,
+                    _ => sound.duration(),
+                };
+                (key, stamp, duration)
+            })
+            .collect();
+
+        probed
+            .into_iter()
+            .map(|(key, stamp, duration)| {
+                if let Some(stamp) = stamp {
+                    self.entries.insert(key, Entry { stamp, duration });
+                }
+                duration
+            })
+            .collect()
+    }
+
+    /// Persist the cache to disk
+    pub fn save(&self) -> io::Result<()> {
+        state::save_json(&self.path, self)
+    }
+}
+
+fn stamp(path: &Path) -> Option<Stamp> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(Stamp {
+        size: metadata.len(),
+        modified_secs: modified.as_secs(),
+    })
+}
@@ -0,0 +1,35 @@
+//! Exit codes for outcomes that scripts tell apart, such as a blocked play.
+
+use std::fmt;
+use std::process::ExitCode;
+
+/// Exit code when playback was refused because a blocklisted app is running
+pub const BLOCKED: u8 = 2;
+
+/// An error that should end the process with a specific exit code rather than 1
+#[derive(Debug)]
+pub struct ExitError {
+    pub code: u8,
+    pub message: String,
+}
+
+impl ExitError {
+    pub fn new(code: u8, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn exit_code(&self) -> ExitCode {
+        ExitCode::from(self.code)
+    }
+}
+
+impl fmt::Display for ExitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ExitError {}
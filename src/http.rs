@@ -0,0 +1,249 @@
+//! HTTP client setup shared by every network request cue makes.
+
+use crate::config::Config;
+use serde::{Deserialize, Serialize, Serializer};
+use std::env;
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Wait before the first retry of a failed request; doubled for each one after
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest wait between retries, however many there have been
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Hosts cue talks to (the GitHub API and release downloads), checked
+/// against `no_proxy` to decide whether to bypass the proxy
+const HOSTS: &[&str] = &[
+    "api.github.com",
+    "github.com",
+    "objects.githubusercontent.com",
+];
+
+/// Proxy variables in the order they're consulted. Requests are all HTTPS,
+/// so the `https_proxy` variants win over `http_proxy`.
+const PROXY_VARS: &[&str] = &["https_proxy", "HTTPS_PROXY", "http_proxy", "HTTP_PROXY"];
+
+/// HTTP agent plus the retry policy applied to every request made through it
+pub struct Client {
+    agent: ureq::Agent,
+    max_retries: u32,
+}
+
+impl Client {
+    /// Client built by `build_http_agent`, retrying failed requests up to
+    /// `max_retries` times (0 disables retries)
+    pub fn new(config: &Config, max_retries: u32) -> Self {
+        Self {
+            agent: build_http_agent(config),
+            max_retries,
+        }
+    }
+
+    /// GET `url`, retrying network errors and server errors with backoff
+    pub fn get(&self, url: &str) -> Result<ureq::Response, Box<ureq::Error>> {
+        retry_request(self.max_retries.saturating_add(1), RETRY_BASE_DELAY, || {
+            self.agent.get(url).call().map_err(Box::new)
+        })
+    }
+}
+
+/// Call `f` up to `attempts` times, waiting `base_delay` before the first retry
+/// and twice as long before each one after, up to `RETRY_MAX_DELAY`. Only
+/// network failures and 5xx responses are retried; anything else, such as a
+/// 404, is returned at once.
+pub fn retry_request<T>(
+    attempts: u32,
+    base_delay: Duration,
+    f: impl Fn() -> Result<T, Box<ureq::Error>>,
+) -> Result<T, Box<ureq::Error>> {
+    let mut delay = base_delay;
+
+    for attempt in 1.. {
+        match f() {
+            Err(e) if attempt < attempts && is_retryable(&e) => {
+                eprintln!(
+                    "Request failed ({}); retrying in {}s (attempt {}/{})",
+                    e,
+                    delay.as_secs_f64(),
+                    attempt + 1,
+                    attempts
+                );
+                thread::sleep(delay);
+                delay = delay.saturating_mul(2).min(RETRY_MAX_DELAY);
+            }
+            result => return result,
+        }
+    }
+
+    unreachable!("the final attempt always returns")
+}
+
+fn is_retryable(error: &ureq::Error) -> bool {
+    match error {
+        ureq::Error::Transport(_) => true,
+        ureq::Error::Status(status, _) => *status >= 500,
+    }
+}
+
+/// A credential that must never show up in logs, errors or `cue config show`.
+/// Debug-printing or serializing it gives `***`.
+#[derive(Clone, Deserialize)]
+#[serde(transparent)]
+pub struct SecretToken(String);
+
+impl SecretToken {
+    /// The token itself, for the one place it's needed: the request header
+    fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\"***\"")
+    }
+}
+
+impl Serialize for SecretToken {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str("***")
+    }
+}
+
+/// Build the agent for all HTTP requests, going through a proxy when one is
+/// configured. `proxy` in the config takes precedence over the environment;
+/// otherwise the usual proxy variables are read, honouring `no_proxy`.
+///
+/// Requests to GitHub are authenticated when GITHUB_TOKEN or `github_token`
+/// in the config is set (the environment wins), which lifts the API rate limit.
+pub fn build_http_agent(config: &Config) -> ureq::Agent {
+    let mut builder = ureq::AgentBuilder::new().user_agent("cue-cli");
+
+    if let Some(token) = github_token(config) {
+        debug!("Authenticating GitHub requests with a token");
+        builder = builder.middleware(GithubAuth(token));
+    }
+
+    if let Some(url) = proxy_url(config) {
+        match ureq::Proxy::new(&url) {
+            Ok(proxy) => {
+                debug!(proxy = %url, "Using HTTP proxy");
+                builder = builder.proxy(proxy);
+            }
+            Err(e) => warn!(proxy = %url, error = %e, "Ignoring invalid proxy"),
+        }
+    }
+
+    builder.build()
+}
+
+/// Adds the token to requests bound for GitHub. ureq already drops the
+/// header when a download redirects elsewhere.
+struct GithubAuth(SecretToken);
+
+impl ureq::Middleware for GithubAuth {
+    fn handle(
+        &self,
+        request: ureq::Request,
+        next: ureq::MiddlewareNext,
+    ) -> Result<ureq::Response, ureq::Error> {
+        let request = if HOSTS.contains(&request.request_url()?.host()) {
+            request.set("Authorization", &format!("token {}", self.0.expose()))
+        } else {
+            request
+        };
+
+        next.handle(request)
+    }
+}
+
+fn github_token(config: &Config) -> Option<SecretToken> {
+    env::var("GITHUB_TOKEN")
+        .ok()
+        .filter(|t| !t.is_empty())
+        .map(SecretToken)
+        .or_else(|| config.github_token.clone())
+        .filter(|t| !t.expose().is_empty())
+}
+
+fn proxy_url(config: &Config) -> Option<String> {
+    if let Some(proxy) = config.proxy.as_ref().filter(|p| !p.is_empty()) {
+        return Some(proxy.clone());
+    }
+
+    let url = PROXY_VARS
+        .iter()
+        .find_map(|var| env::var(var).ok().filter(|v| !v.is_empty()))?;
+
+    if bypasses_proxy() {
+        debug!("All hosts match no_proxy; connecting directly");
+        return None;
+    }
+
+    Some(url)
+}
+
+/// Whether `no_proxy` (or `NO_PROXY`) excludes the hosts cue connects to
+fn bypasses_proxy() -> bool {
+    ["no_proxy", "NO_PROXY"]
+        .iter()
+        .find_map(|var| env::var(var).ok())
+        .is_some_and(|no_proxy| excludes_all(&no_proxy, HOSTS))
+}
+
+/// Whether a `no_proxy` value excludes every one of `hosts`. Entries are host
+/// names or domain suffixes (`.github.com`); `*` matches all.
+fn excludes_all(no_proxy: &str, hosts: &[&str]) -> bool {
+    if no_proxy.trim() == "*" {
+        return true;
+    }
+
+    let entries: Vec<&str> = no_proxy
+        .split(',')
+        .map(|entry| entry.trim().trim_start_matches('*'))
+        .filter(|entry| !entry.is_empty())
+        .collect();
+
+    hosts.iter().all(|host| {
+        entries.iter().any(|entry| {
+            let domain = entry.trim_start_matches('.');
+            *host == domain || host.ends_with(&format!(".{}", domain))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcard_excludes_everything() {
+        assert!(excludes_all("*", HOSTS));
+        assert!(excludes_all(" * ", HOSTS));
+    }
+
+    #[test]
+    fn domain_suffixes_cover_subdomains() {
+        assert!(excludes_all(".github.com,githubusercontent.com", HOSTS));
+        assert!(excludes_all(
+            "*.github.com, github.com, *.githubusercontent.com",
+            HOSTS
+        ));
+    }
+
+    #[test]
+    fn every_host_must_be_excluded() {
+        assert!(!excludes_all("github.com", &["github.com", "example.org"]));
+        assert!(!excludes_all("api.github.com", HOSTS));
+        assert!(!excludes_all("", HOSTS));
+    }
+
+    #[test]
+    fn suffixes_match_whole_labels() {
+        assert!(!excludes_all("hub.com", &["github.com"]));
+        assert!(excludes_all("github.com", &["api.github.com"]));
+    }
+}
@@ -0,0 +1,61 @@
+//! Structured log events written to the file configured under `[logging]`.
+
+use crate::config::LoggingConfig;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::str::FromStr;
+use std::sync::Mutex;
+use thiserror::Error;
+use tracing_subscriber::filter::LevelFilter;
+
+#[derive(Debug, Error)]
+pub enum LoggingError {
+    #[error("Failed to open log file: {0}")]
+    FileError(#[from] io::Error),
+
+    #[error("Invalid log level: {0}")]
+    InvalidLevel(String),
+
+    #[error("Invalid log format: {0} (expected 'text' or 'json')")]
+    InvalidFormat(String),
+}
+
+/// Install a global tracing subscriber writing to the configured log file.
+/// Does nothing if no log file is configured, so events are discarded.
+/// With `verbose` (`--verbose`) the file gets at least debug events.
+pub fn init(config: &LoggingConfig, verbose: bool) -> Result<(), LoggingError> {
+    let Some(path) = &config.file else {
+        return Ok(());
+    };
+
+    let mut level = LevelFilter::from_str(&config.level)
+        .map_err(|_| LoggingError::InvalidLevel(config.level.clone()))?;
+    if verbose {
+        level = level.max(LevelFilter::DEBUG);
+    }
+
+    let json = match config.format.to_lowercase().as_str() {
+        "text" => false,
+        "json" => true,
+        _ => return Err(LoggingError::InvalidFormat(config.format.clone())),
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(Mutex::new(file))
+        .with_ansi(false);
+
+    if json {
+        builder.json().init();
+    } else {
+        builder.init();
+    }
+
+    Ok(())
+}
@@ -1,84 +1,814 @@
 mod archive;
 mod cli;
+mod color;
 mod config;
+mod context;
+mod dedup;
+mod duck;
+mod duration_cache;
+mod exit;
+mod http;
+mod logging;
+mod output;
+mod picker;
+mod pidfile;
 mod playback;
+mod playlist;
+mod queue;
+mod rate_limit;
+mod search;
+mod semaphore;
+mod state;
+mod stats;
 mod suppression;
+mod tui;
 mod upgrade;
+mod visualization;
 
-use archive::{ArchiveError, SoundArchive};
-use clap::Parser;
-use cli::{Cli, Command};
-use config::Config;
-use playback::Player;
+use archive::{ArchiveError, AudioFormat, ImportMode, PermissionFix, SoundArchive, SoundFile};
+use clap::builder::PossibleValuesParser;
+use clap::{CommandFactory, Parser, ValueEnum};
+use clap_complete::Shell;
+use cli::{
+    ArchiveCommand, BlocklistCommand, Cli, Command, ConfigCommand, OutputFormat, QueueCommand,
+    RenameCommand,
+};
+use color::Palette;
+use config::{Config, ConfigError, EmptyCategoryAction, InitOptions};
+use context::Context;
+use dedup::DedupCache;
+use duck::DuckGuard;
+use duration_cache::DurationCache;
+use exit::ExitError;
+use output::{
+    CategoryEntry, CategoryPath, DeviceEntry, FormatGroup, SearchEntry, SoundEntry, SoundPath,
+};
+use picker::{BalancedCategoryPicker, StrategyState};
+use pidfile::PidFile;
+use playback::{LoopCount, PlaybackError, PlaybackOptions, Player};
+use playlist::m3u::M3UParser;
+use queue::{Queue, QueueEntry};
+use rand::Rng;
+use rate_limit::RateLimiter;
+use semaphore::GlobalSemaphore;
+use stats::{ArchiveStats, CategorySummary, DurationStats, LatencyReport, PlayStats};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
-use suppression::{ProcessDetector, SuppressionResult};
+use std::thread;
+use std::time::{Duration, Instant};
+use suppression::{ProcessDetector, QuietHoursAction, SuppressionResult};
+use upgrade::UpgradeOptions;
+
+/// Column width of waveforms printed by `cue preview --waveform`
+const WAVEFORM_WIDTH: usize = 80;
 
 fn main() -> ExitCode {
+    #[cfg(windows)]
+    upgrade::remove_stale_backup();
+
     let cli = Cli::parse();
+    let json_errors = matches!(
+        cli.command,
+        Command::List { json: true, .. }
+            | Command::List {
+                format: OutputFormat::Json,
+                ..
+            }
+    );
+
+    let result = run(cli);
 
-    if let Err(e) = run(cli) {
-        eprintln!("Error: {e}");
-        return ExitCode::from(1);
+    // Playback stopped by a signal; everything has unwound, so just report it
+    if playback::interrupted() {
+        return ExitCode::from(130);
     }
 
-    ExitCode::SUCCESS
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            tracing::error!(error = %e, "Command failed");
+            if json_errors {
+                println!("{}", serde_json::json!({ "error": e.to_string() }));
+            } else {
+                eprintln!("Error: {e}");
+            }
+            match e.downcast_ref::<ExitError>() {
+                Some(exit) => exit.exit_code(),
+                None => ExitCode::from(1),
+            }
+        }
+    }
 }
 
-fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
-    // Handle upgrade separately (doesn't need config/archive)
-    if let Command::Upgrade { check } = cli.command {
-        return run_upgrade(check);
+fn run(cli: Cli) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let started = Instant::now();
+    let ctx = Context {
+        dry_run: cli.dry_run,
+        verbose: cli.verbose,
+        quiet: cli.quiet,
+    };
+
+    // Config commands must work even when the config file no longer parses
+    if let Command::Config { command } = &cli.command {
+        return run_config(command, &ctx).map(|()| ExitCode::SUCCESS);
+    }
+
+    if let Command::Completions { shell } = &cli.command {
+        return run_completions(*shell).map(|()| ExitCode::SUCCESS);
+    }
+
+    // Device listing is for diagnosing audio problems, so it needs no config or archive
+    if let Command::Devices {
+        default,
+        format,
+        json,
+    } = &cli.command
+    {
+        return run_devices(*default, *json || *format == OutputFormat::Json)
+            .map(|()| ExitCode::SUCCESS);
+    }
+
+    // Emergency path: play a known file with no config, archive, or blocklist
+    if let Command::Play {
+        skip_archive_load: true,
+        path: Some(path),
+        volume,
+        fade_in,
+        fade_out,
+        device,
+        speed,
+        progress,
+        ..
+    } = &cli.command
+    {
+        let opts = PlaybackOptions::new()
+            .volume(volume.unwrap_or(100) as f32 / 100.0)
+            .speed(*speed)
+            .fade_in(*fade_in)
+            .fade_out(*fade_out)
+            .progress(*progress);
+        let player = match device {
+            _ if ctx.dry_run => Player::dry_run(),
+            Some(name) => Player::new_with_device(name)?,
+            None => Player::new()?,
+        };
+        player.play_with_options(path, opts)?;
+        return Ok(ExitCode::SUCCESS);
     }
 
-    let config = Config::load()?;
-    let archive = SoundArchive::load(&config.sounds_path)?;
+    // Upgrading is how a config written for a newer release gets fixed, so
+    // these commands fall back to the defaults when the config doesn't load
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e)
+            if matches!(
+                cli.command,
+                Command::Upgrade { .. } | Command::Changelog { .. } | Command::Rollback { .. }
+            ) =>
+        {
+            ctx.warn(format_args!("using the default settings. {}", e));
+            Config::default()
+        }
+        Err(e) => return Err(e.into()),
+    };
+    logging::init(&config.logging, ctx.verbose)?;
+
+    // Sounds from before the XDG data directory default keep working until moved
+    if let (true, Some(legacy), false) = (
+        config.sounds_path.is_empty(),
+        Config::legacy_sounds_path(),
+        ctx.quiet,
+    ) {
+        eprintln!(
+            "Note: sounds are in the old default location {}; move them to {} or set sounds_path",
+            legacy.display(),
+            Config::data_sounds_path().display()
+        );
+    }
+
+    // Handle commands that don't need a loaded archive
+    let command = match cli.command {
+        Command::Upgrade {
+            check,
+            changelog,
+            self_test,
+            release_list,
+            limit,
+            version,
+            channel,
+            no_backup,
+            force,
+            no_retry,
+        } => {
+            let retries = if no_retry { 0 } else { config.max_retries };
+            let client = http::Client::new(&config, retries);
+
+            if release_list {
+                return run_release_list(&client, limit).map(|()| ExitCode::SUCCESS);
+            }
+
+            let options = UpgradeOptions {
+                self_test,
+                version,
+                keep_backup: config.upgrade.keep_backup && !no_backup,
+                max_backups: config.upgrade.max_backups,
+                force,
+                channel: channel.unwrap_or(config.upgrade.channel),
+                dry_run: ctx.dry_run,
+            };
+            return run_upgrade(&client, check, changelog, &options, &ctx)
+                .map(|()| ExitCode::SUCCESS);
+        }
+        Command::Changelog {
+            version,
+            format,
+            json,
+        } => {
+            let client = http::Client::new(&config, config.max_retries);
+            let json = json || format == OutputFormat::Json;
+            return run_changelog(&client, version.as_deref(), config.upgrade.channel, json)
+                .map(|()| ExitCode::SUCCESS);
+        }
+        Command::Rollback { to } => {
+            let options = UpgradeOptions {
+                keep_backup: config.upgrade.keep_backup,
+                max_backups: config.upgrade.max_backups,
+                dry_run: ctx.dry_run,
+                ..Default::default()
+            };
+            return run_rollback(to.as_deref(), &options, &ctx).map(|()| ExitCode::SUCCESS);
+        }
+        Command::Blocklist { command } => {
+            return run_blocklist(&config, command, &ctx).map(|()| ExitCode::SUCCESS)
+        }
+        Command::Archive { command } => {
+            return run_archive(&config, command, &ctx).map(|()| ExitCode::SUCCESS)
+        }
+        Command::Queue { command } => {
+            return run_queue(&config, command, &ctx).map(|()| ExitCode::SUCCESS)
+        }
+        Command::Add {
+            category,
+            paths,
+            move_files,
+        } => {
+            return run_add(&config, &category, &paths, move_files, &ctx)
+                .map(|()| ExitCode::SUCCESS)
+        }
+        command => command,
+    };
+
+    let load_started = Instant::now();
+    let archive = load_archive(&config, &ctx)?.with_aliases(&config.aliases);
+    ctx.detail(format_args!(
+        "Loaded {} categories ({} sounds) in {:.1}ms",
+        archive.categories.len(),
+        archive
+            .categories
+            .iter()
+            .map(|c| c.sounds.len())
+            .sum::<usize>(),
+        load_started.elapsed().as_secs_f64() * 1000.0
+    ));
 
-    match cli.command {
+    match command {
         Command::Play {
             category,
             volume,
             force,
+            duration_stats,
+            category_stats_only,
+            format,
+            json,
+            print_path,
+            pick_strategy,
+            exclude,
+            exclude_format,
+            reset,
+            timeout_random,
+            on_empty_category,
+            fallback_category,
+            rate_limit,
+            rate_limit_timeout,
+            confirm,
+            mix_volume,
+            max_concurrent_globally,
+            wait,
+            category_stats_json,
+            interleave,
+            balance_categories,
+            count,
+            no_replace,
+            wrap,
+            repeat,
+            duration,
+            min_sounds_required,
+            output_format,
+            require_blocklist_clear,
+            measure_latency,
+            on_error_play,
+            pid_file,
+            replace_pid_file,
+            report_exit_code_on_blocked,
+            report_exit_code_on_empty,
+            format_warning,
+            suppress_duplicate_within,
+            latency_mode,
+            fade_in,
+            fade_out,
+            device,
+            speed,
+            progress,
+            ..
         } => {
-            // Check for blocking apps unless --force is set
-            if !force {
-                let mut detector = ProcessDetector::new();
-                if let SuppressionResult::Blocked { app_name } =
-                    detector.check_blocklist(&config.blocklist)
-                {
-                    println!("Skipped: {} is running", app_name);
-                    return Ok(());
+            let device = device.or_else(|| config.device.clone());
+            let volume = volume.unwrap_or(config.default_volume);
+            let format = if json { OutputFormat::Json } else { format };
+
+            // Custom exit codes for the non-error outcomes; both default to success
+            let blocked_code = report_exit_code_on_blocked.unwrap_or(0);
+            let empty_exit = ExitCode::from(report_exit_code_on_empty.unwrap_or(0));
+
+            // A dry run writes no PID file and doesn't coordinate with other processes
+            let pid_file = pid_file.filter(|_| !ctx.dry_run);
+            let rate_limit = rate_limit.filter(|_| !ctx.dry_run);
+            let max_concurrent_globally = max_concurrent_globally.filter(|_| !ctx.dry_run);
+            let mix_volume = mix_volume.filter(|_| !ctx.dry_run);
+
+            let _pid_file = match pid_file {
+                Some(path) => {
+                    let pid_file = PidFile::create(&path, replace_pid_file)?;
+                    // Stop on Ctrl-C or `kill` by unwinding, so the file is removed on drop
+                    playback::stop_on_interrupt()?;
+                    Some(pid_file)
+                }
+                None => None,
+            };
+
+            if let Some(categories) = interleave {
+                if is_blocked(
+                    &ctx,
+                    &config,
+                    None,
+                    force,
+                    require_blocklist_clear,
+                    blocked_code,
+                )? {
+                    return Ok(ExitCode::from(blocked_code));
+                }
+
+                let names: Vec<&str> = categories.iter().map(String::as_str).collect();
+                if let Some(missing) = names.iter().find(|n| archive.category(n).is_none()) {
+                    return Err(ArchiveError::CategoryNotFound(missing.to_string()).into());
+                }
+
+                let count = match repeat {
+                    Some(0) => usize::MAX,
+                    Some(rounds) => rounds as usize * names.len(),
+                    None => count.unwrap_or(names.len()),
+                };
+
+                let player = ctx.player(
+                    device.as_deref(),
+                    latency_mode.unwrap_or(config.latency_mode),
+                )?;
+                let opts = PlaybackOptions::new()
+                    .speed(speed)
+                    .fade_in(fade_in)
+                    .fade_out(fade_out)
+                    .progress(progress)
+                    .follow_ducking(mix_volume.is_none());
+                let sounds = archive.interleaved_random(&names);
+                for (i, sound) in sounds.take(count).enumerate() {
+                    // Categories are taken in turn, so the index gives this sound's category
+                    let category = archive.category(names[i % names.len()]);
+                    let vol = config.volume_for(category.map_or("", |c| &c.name), volume);
+                    player.play_with_options(&sound?.path, opts.volume(vol))?;
+                }
+
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            if balance_categories {
+                if is_blocked(
+                    &ctx,
+                    &config,
+                    None,
+                    force,
+                    require_blocklist_clear,
+                    blocked_code,
+                )? {
+                    return Ok(ExitCode::from(blocked_code));
+                }
+
+                let count = match repeat {
+                    Some(0) => usize::MAX,
+                    Some(rounds) => rounds as usize,
+                    None => count.unwrap_or(1),
+                };
+
+                let player = ctx.player(
+                    device.as_deref(),
+                    latency_mode.unwrap_or(config.latency_mode),
+                )?;
+                let opts = PlaybackOptions::new()
+                    .speed(speed)
+                    .fade_in(fade_in)
+                    .fade_out(fade_out)
+                    .progress(progress)
+                    .follow_ducking(mix_volume.is_none());
+                let mut dry_run_picker = BalancedCategoryPicker::load();
+                for _ in 0..count {
+                    let cat = if ctx.dry_run {
+                        dry_run_picker.pick(&archive)
+                    } else {
+                        BalancedCategoryPicker::pick_and_save(&archive)?
+                    }
+                    .ok_or("No categories with sounds to play")?;
+
+                    if let Some(sound) = cat.random() {
+                        tracing::debug!(category = %cat.name, sound = %sound.name, "Balanced pick");
+                        let vol = config.volume_for(&cat.name, volume);
+                        player.play_with_options(&sound.path, opts.volume(vol))?;
+                    }
                 }
+
+                return Ok(ExitCode::SUCCESS);
             }
 
+            let Some(category) = category.or_else(|| config.default_category.clone()) else {
+                return Err(
+                    "No category given; pass one or set default_category in config.toml".into(),
+                );
+            };
+
+            let _span = tracing::info_span!("play", %category).entered();
+
             let cat = archive
                 .category(&category)
                 .ok_or_else(|| ArchiveError::CategoryNotFound(category.clone()))?;
 
-            let sound = cat
-                .random()
-                .ok_or_else(|| ArchiveError::EmptyCategory(category.clone()))?;
+            if category_stats_only {
+                let summary = CategorySummary::compute(cat);
+                match format {
+                    OutputFormat::Text => println!("{}", summary),
+                    OutputFormat::Json => println!("{}", serde_json::to_string(&summary)?),
+                }
+                return Ok(ExitCode::SUCCESS);
+            }
 
-            let player = Player::new()?;
-            let vol = volume as f32 / 100.0;
-            player.play(&sound.path, vol)?;
-        }
+            // Empty categories are left to the on-empty-category handling below
+            let required = min_sounds_required.unwrap_or(config.min_sounds_required) as usize;
+            if !cat.sounds.is_empty() && cat.sounds.len() < required {
+                return Err(ArchiveError::InsufficientSounds {
+                    category,
+                    found: cat.sounds.len(),
+                    required,
+                }
+                .into());
+            }
+
+            // Check for blocking apps unless --force is set; --print-path plays nothing
+            if is_blocked(
+                &ctx,
+                &config,
+                Some(&cat.name),
+                force || print_path,
+                require_blocklist_clear,
+                blocked_code,
+            )? {
+                return Ok(ExitCode::from(blocked_code));
+            }
 
-        Command::List { category } => {
-            if let Some(cat_name) = category {
-                let cat = archive
-                    .category(&cat_name)
-                    .ok_or_else(|| ArchiveError::CategoryNotFound(cat_name.clone()))?;
+            if duration_stats {
+                let mut cache = DurationCache::load();
+                let durations = cache.durations(cat);
+                match DurationStats::from_durations(&durations) {
+                    Some(stats) => eprintln!("Durations in '{}':\n{}", cat.display_name, stats),
+                    None => eprintln!("Durations in '{}': unknown", cat.display_name),
+                }
+
+                // With --dry-run this is just a summary of the category
+                if ctx.dry_run {
+                    return Ok(ExitCode::SUCCESS);
+                }
+                cache.save()?;
+            }
+
+            // Empty categories fall through to the on-empty-category handling below
+            if let Some(count) = count.filter(|_| !cat.sounds.is_empty()) {
+                let sounds = cat.random_many(count, !no_replace, wrap)?;
+                let player = ctx.player(
+                    device.as_deref(),
+                    latency_mode.unwrap_or(config.latency_mode),
+                )?;
+                let opts = PlaybackOptions::new()
+                    .volume(config.volume_for(&cat.name, volume))
+                    .speed(speed)
+                    .fade_in(fade_in)
+                    .fade_out(fade_out)
+                    .progress(progress)
+                    .follow_ducking(mix_volume.is_none());
+                let _duck = mix_volume.map(DuckGuard::engage).transpose()?;
+
+                for sound in sounds {
+                    ctx.detail(format_args!("Playing {}/{}", cat.name, sound.name));
+                    if (format_warning || config.warn_lossy) && sound.format.is_lossy() {
+                        ctx.warn(format_args!(
+                            "playing lossy format ({}). Consider using WAV or FLAC for higher quality.",
+                            sound.format.name()
+                        ));
+                    }
+                    player.play_with_options(&sound.path, opts)?;
+                }
+
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let excluding = !exclude.is_empty() || !exclude_format.is_empty();
+            if excluding && pick_strategy.is_stateful() {
+                return Err("--exclude and --exclude-format only work with random picks".into());
+            }
+
+            let mut dedup = suppress_duplicate_within
+                .map(|secs| (DedupCache::load(&cat.name), Duration::from_secs(secs)));
 
-                println!("Sounds in '{}':", cat.name);
-                for sound in &cat.sounds {
-                    println!("  {}", sound.name);
+            // Saved only once the pick is used, so a rate-limited or declined
+            // play doesn't advance the sequence
+            let mut strategy_state = None;
+
+            let picked = if let Some((cache, window)) = &dedup {
+                cache.pick(cat, *window)
+            } else if pick_strategy.is_stateful() || reset {
+                let mut state = StrategyState::load()?;
+                if reset {
+                    state.sequence(cat).reset();
                 }
+                let sound = pick_strategy.pick(cat, &mut state);
+                strategy_state = Some(state);
+                sound
+            } else if excluding && !cat.sounds.is_empty() {
+                let exclude: Vec<&str> = exclude.iter().map(String::as_str).collect();
+                // Everything excluded is an error even with --on-empty-category
+                Some(
+                    cat.random_excluding(&exclude, &exclude_format)
+                        .ok_or_else(|| ArchiveError::EmptyCategory(cat.name.clone()))?,
+                )
             } else {
-                println!("Available categories:");
-                for cat in &archive.categories {
-                    let count = cat.sounds.len();
-                    let plural = if count == 1 { "sound" } else { "sounds" };
-                    println!("  {} ({} {})", cat.name, count, plural);
+                cat.random()
+            };
+
+            let sound = match picked {
+                Some(sound) => sound,
+                None => match on_empty_category.unwrap_or(config.empty_category_action) {
+                    EmptyCategoryAction::Error => {
+                        let err = ArchiveError::EmptyCategory(category);
+                        return match report_exit_code_on_empty {
+                            Some(code) => Err(ExitError::new(code, err.to_string()).into()),
+                            None => Err(err.into()),
+                        };
+                    }
+                    EmptyCategoryAction::Skip => return Ok(empty_exit),
+                    EmptyCategoryAction::Warn => {
+                        ctx.warn(format_args!(
+                            "category '{}' is empty; nothing played",
+                            cat.display_name
+                        ));
+                        return Ok(empty_exit);
+                    }
+                    EmptyCategoryAction::UseFallback => {
+                        let fallback = fallback_category
+                            .or_else(|| config.fallback_category.clone())
+                            .ok_or("use-fallback needs --fallback-category or fallback_category in the config")?;
+                        archive
+                            .category(&fallback)
+                            .ok_or_else(|| ArchiveError::CategoryNotFound(fallback.clone()))?
+                            .random()
+                            .ok_or(ArchiveError::EmptyCategory(fallback))?
+                    }
+                },
+            };
+
+            ctx.detail(format_args!(
+                "Selected {}/{} ({})",
+                cat.name,
+                sound.name,
+                sound.path.display()
+            ));
+            let mut save_strategy_state = || match strategy_state.take() {
+                Some(state) if !ctx.dry_run => state.save(),
+                _ => Ok(()),
+            };
+
+            if print_path {
+                let entry = SoundPath::new(cat, sound);
+                match format {
+                    OutputFormat::Text => println!("{}", entry.path.display()),
+                    OutputFormat::Json => println!("{}", serde_json::to_string(&entry)?),
+                }
+
+                // The caller plays it, so it counts towards duplicate suppression
+                save_strategy_state()?;
+                if let (Some((cache, _)), false) = (&mut dedup, ctx.dry_run) {
+                    cache.record(sound)?;
+                }
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            if (format_warning || config.warn_lossy) && sound.format.is_lossy() {
+                ctx.warn(format_args!(
+                    "playing lossy format ({}). Consider using WAV or FLAC for higher quality.",
+                    sound.format.name()
+                ));
+            }
+
+            if category_stats_json {
+                let stats = PlayStats::new(cat, &sound.name);
+                println!("{}", serde_json::to_string(&stats)?);
+            }
+
+            if confirm {
+                let prompt = format!("About to play: {}/{} — play? [Y/n]: ", cat.name, sound.name);
+                if !ask_yes_no(&prompt, true)? {
+                    return Ok(ExitCode::SUCCESS);
+                }
+            }
+
+            if let Some(raw_format) = output_format {
+                let audio = playback::decode(&sound.path)?;
+                eprintln!(
+                    "rate={} channels={} format={}",
+                    audio.sample_rate,
+                    audio.channels,
+                    raw_format
+                        .to_possible_value()
+                        .expect("no skipped variants")
+                        .get_name()
+                );
+                save_strategy_state()?;
+                playback::write_raw(
+                    &audio.samples,
+                    config.volume_for(&cat.name, volume),
+                    raw_format,
+                    &mut io::stdout().lock(),
+                )?;
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            if let Some(per_minute) = rate_limit {
+                let limiter = RateLimiter::new(per_minute);
+                let acquired =
+                    limiter.acquire(Duration::from_secs(rate_limit_timeout), |wait| {
+                        ctx.warn(format_args!(
+                            "rate limit reached; waiting {:.1}s",
+                            wait.as_secs_f64()
+                        ))
+                    })?;
+
+                if !acquired {
+                    ctx.say(format_args!(
+                        "Skipped: rate limit of {} plays per minute reached",
+                        per_minute
+                    ));
+                    return Ok(ExitCode::SUCCESS);
+                }
+            }
+
+            let _slot = match max_concurrent_globally {
+                Some(slots) if wait => Some(GlobalSemaphore::acquire(slots)?),
+                Some(slots) => match GlobalSemaphore::try_acquire(slots)? {
+                    Some(slot) => Some(slot),
+                    None => return Ok(ExitCode::SUCCESS),
+                },
+                None => None,
+            };
+            save_strategy_state()?;
+
+            let player = ctx.player(
+                device.as_deref(),
+                latency_mode.unwrap_or(config.latency_mode),
+            )?;
+            let opts = PlaybackOptions::new()
+                .volume(config.volume_for(&cat.name, volume))
+                .speed(speed)
+                .fade_in(fade_in)
+                .fade_out(fade_out)
+                .loop_count(repeat.map(LoopCount::from).unwrap_or_default())
+                .progress(progress)
+                .follow_ducking(mix_volume.is_none());
+            let _duck = mix_volume.map(DuckGuard::engage).transpose()?;
+
+            let limit = timeout_random
+                .map(|range| {
+                    let timeout = rand::thread_rng().gen_range(range.min..=range.max);
+                    tracing::debug!(?timeout, "Chose random playback timeout");
+                    timeout
+                })
+                .or(duration);
+
+            let play_called = Instant::now();
+            let first_sample = match (
+                player.play_measured(&sound.path, opts, limit),
+                &on_error_play,
+            ) {
+                (Ok(first_sample), _) => first_sample,
+                (
+                    Err(e @ (PlaybackError::FileError(_) | PlaybackError::DecodeError(_))),
+                    Some(fallback),
+                ) => {
+                    tracing::warn!(error = %e, sound = %sound.name, %fallback, "Playing fallback category");
+                    ctx.warn(format_args!(
+                        "failed to play {}/{}: {}",
+                        cat.name, sound.name, e
+                    ));
+
+                    // Errors from the fallback itself are returned, not retried
+                    let fallback_cat = archive
+                        .category(fallback)
+                        .ok_or_else(|| ArchiveError::CategoryNotFound(fallback.clone()))?;
+                    let fallback_sound = fallback_cat
+                        .random()
+                        .ok_or_else(|| ArchiveError::EmptyCategory(fallback.clone()))?;
+                    let fallback_opts = opts.volume(config.volume_for(&fallback_cat.name, volume));
+                    player.play_with_options(&fallback_sound.path, fallback_opts)?;
+                    None
+                }
+                (Err(e), _) => return Err(e.into()),
+            };
+
+            if let (Some((cache, _)), false) = (&mut dedup, ctx.dry_run) {
+                cache.record(sound)?;
+            }
+
+            if measure_latency {
+                let latency = LatencyReport {
+                    time_to_first_sample_ms: first_sample.map(|d| d.as_secs_f64() * 1000.0),
+                    total_setup_ms: (play_called - started + first_sample.unwrap_or_default())
+                        .as_secs_f64()
+                        * 1000.0,
+                };
+
+                match format {
+                    OutputFormat::Text => eprintln!("{}", latency),
+                    OutputFormat::Json => println!("{}", serde_json::to_string(&latency)?),
+                }
+            }
+        }
+
+        Command::List {
+            category,
+            pattern,
+            category_path,
+            group_by_format,
+            sounds_path_prefix,
+            verbose,
+            watch,
+            format,
+            json,
+            pretty,
+        } => {
+            let palette = Palette::new(cli.color, &config.colors);
+            let options = ListOptions {
+                category_path,
+                group_by_format,
+                sounds_path_prefix,
+                verbose,
+                quiet: ctx.quiet,
+                json: json || format == OutputFormat::Json,
+                pretty,
+            };
+            run_list(
+                &archive,
+                category.clone(),
+                pattern.clone(),
+                options.clone(),
+                &palette,
+            )?;
+
+            if watch {
+                let aliases = config.aliases.clone();
+                let _watch = archive.watch(move |archive| {
+                    let archive = archive.with_aliases(&aliases);
+                    println!();
+                    if let Err(e) = run_list(
+                        &archive,
+                        category.clone(),
+                        pattern.clone(),
+                        options.clone(),
+                        &palette,
+                    ) {
+                        if options.json {
+                            println!("{}", serde_json::json!({ "error": e.to_string() }));
+                        } else {
+                            eprintln!("Error: {}", e);
+                        }
+                    }
+                })?;
+
+                loop {
+                    thread::park();
                 }
             }
         }
@@ -87,46 +817,1535 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             category,
             sound,
             volume,
+            waveform,
+            waveform_only,
+            export,
+            no_play,
+            print_path,
+            json,
+            repeat,
+            speed_sweep,
+            confirm,
+            gap,
+            dry_run,
+            fade_in,
+            fade_out,
+            duration,
+            device,
+            speed,
+            progress,
         } => {
+            let _span = tracing::info_span!("preview", %category, %sound).entered();
+            let volume = volume.unwrap_or(config.default_volume);
+
             let cat = archive
                 .category(&category)
                 .ok_or_else(|| ArchiveError::CategoryNotFound(category.clone()))?;
 
+            let matches = if archive::is_glob(&sound) {
+                cat.sounds_matching(&sound)
+            } else {
+                cat.sound(&sound).into_iter().collect()
+            };
+
+            if matches.is_empty() {
+                return Err(ArchiveError::SoundNotFound(format!(
+                    "'{}' in category '{}'",
+                    sound, category
+                ))
+                .into());
+            }
+
+            if print_path {
+                for snd in &matches {
+                    let entry = SoundPath::new(cat, snd);
+                    if json {
+                        println!("{}", serde_json::to_string(&entry)?);
+                    } else {
+                        println!("{}", entry.path.display());
+                    }
+                }
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            if waveform || waveform_only {
+                for snd in &matches {
+                    let samples = playback::decode_samples(&snd.path)?;
+                    println!("{}:", snd.name);
+                    println!(
+                        "{}",
+                        visualization::ascii_waveform(&samples, WAVEFORM_WIDTH)
+                    );
+                }
+            }
+
+            if !(waveform_only || no_play) {
+                let device = device.or_else(|| config.device.clone());
+                let player = ctx.player(device.as_deref(), config.latency_mode)?;
+                let vol = config.volume_for(&cat.name, volume);
+                let opts = PlaybackOptions::new()
+                    .volume(vol)
+                    .speed(speed)
+                    .fade_in(fade_in)
+                    .fade_out(fade_out)
+                    .progress(progress);
+                for snd in &matches {
+                    match (repeat, speed_sweep) {
+                        (Some(times), _) => {
+                            let opts = opts
+                                .loop_count(LoopCount::from(times))
+                                .gap(Duration::from_millis(gap));
+                            player.play_repetitions(
+                                &snd.path,
+                                opts,
+                                duration,
+                                |i| match times {
+                                    _ if ctx.quiet => {}
+                                    0 => eprintln!("Playing ({}): {}", i, snd.name),
+                                    _ => eprintln!("Playing ({}/{}): {}", i, times, snd.name),
+                                },
+                            )?;
+                        }
+                        (None, Some(sweep)) => {
+                            let speeds = sweep.speeds();
+                            for (i, speed) in speeds.iter().enumerate() {
+                                if !ctx.quiet {
+                                    eprintln!("Speed: {:.2}x", speed);
+                                }
+                                player.play_with_options(&snd.path, opts.speed(*speed))?;
+
+                                if confirm && i + 1 < speeds.len() {
+                                    eprint!("Press Enter for the next speed...");
+                                    io::stderr().flush()?;
+                                    io::stdin().read_line(&mut String::new())?;
+                                }
+                            }
+                        }
+                        (None, None) => {
+                            player.play_measured(&snd.path, opts, duration)?;
+                        }
+                    }
+                }
+            }
+
+            if let Some(dest) = export {
+                if matches.len() > 1 && !dest.is_dir() {
+                    return Err(format!(
+                        "{} sounds matched; --export must be an existing directory",
+                        matches.len()
+                    )
+                    .into());
+                }
+
+                for snd in &matches {
+                    export_sound(snd, &dest, dry_run || ctx.dry_run, &ctx)?;
+                }
+            }
+        }
+
+        Command::Search {
+            query,
+            limit,
+            fuzzy,
+            play,
+            format,
+            json,
+        } => {
+            let hits: Vec<_> = search::search(&archive, &query, fuzzy)
+                .into_iter()
+                .take(limit)
+                .collect();
+
+            if json || format == OutputFormat::Json {
+                let entries: Vec<_> = hits.iter().map(SearchEntry::new).collect();
+                println!("{}", serde_json::to_string(&entries)?);
+            } else if hits.is_empty() {
+                ctx.say(format_args!("No sounds match '{}'", query));
+            } else {
+                for hit in &hits {
+                    println!("{}/{}", hit.category.name, hit.sound.name);
+                }
+            }
+
+            if let (true, Some(top)) = (play, hits.first()) {
+                let cat = &top.category.name;
+                if !is_blocked(&ctx, &config, Some(cat), false, false, 0)? {
+                    let volume = config.volume_for(cat, config.default_volume);
+                    ctx.player(config.device.as_deref(), config.latency_mode)?
+                        .play(&top.sound.path, volume)?;
+                }
+            }
+        }
+
+        Command::Tui { volume, device } => {
+            let device = device.or_else(|| config.device.clone());
+            let player = ctx.player(device.as_deref(), config.latency_mode)?;
+            let reload = || {
+                SoundArchive::load(&config.all_sounds_paths(), config.archive_options())
+                    .map(|archive| archive.with_aliases(&config.aliases))
+            };
+            tui::run(archive, reload, player, volume as f32 / 100.0)?;
+        }
+
+        Command::Remove {
+            category,
+            sound,
+            yes,
+            dry_run,
+            remove_empty_category,
+        } => {
+            let cat = archive
+                .category(&category)
+                .ok_or_else(|| ArchiveError::CategoryNotFound(category.clone()))?;
             let snd = cat.sound(&sound).ok_or_else(|| {
                 ArchiveError::SoundNotFound(format!("'{}' in category '{}'", sound, category))
             })?;
+            let empties_category = cat.sounds.len() == 1;
+
+            if dry_run || ctx.dry_run {
+                ctx.say(format_args!("Would remove {}", snd.path.display()));
+                if remove_empty_category && empties_category {
+                    ctx.say(format_args!(
+                        "Would remove empty category {}",
+                        cat.path.display()
+                    ));
+                }
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let prompt = format!("Remove {}? Are you sure? [y/N] ", snd.path.display());
+            if !yes && !ask_yes_no(&prompt, false)? {
+                ctx.say("Nothing removed");
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            fs::remove_file(&snd.path)?;
+            ctx.say(format_args!("Removed {}", snd.path.display()));
+
+            if remove_empty_category && empties_category {
+                // Leaves the directory alone if it still holds subcategories or other files
+                match fs::remove_dir(&cat.path) {
+                    Ok(()) => ctx.say(format_args!(
+                        "Removed empty category {}",
+                        cat.path.display()
+                    )),
+                    Err(e) => ctx.warn(format_args!(
+                        "kept category directory {}: {}",
+                        cat.path.display(),
+                        e
+                    )),
+                }
+            }
+        }
+
+        Command::Rename { command } => run_rename(&archive, command, &ctx)?,
+
+        Command::Stats { format, json } => {
+            let stats = ArchiveStats::new(&archive);
+            if json || format == OutputFormat::Json {
+                println!("{}", serde_json::to_string(&stats)?);
+            } else {
+                println!("{}", stats);
+            }
+        }
+
+        Command::Validate { category, fix } => {
+            let categories: Vec<_> = match &category {
+                Some(name) => vec![archive
+                    .category(name)
+                    .ok_or_else(|| ArchiveError::CategoryNotFound(name.clone()))?],
+                None => archive.categories.iter().collect(),
+            };
+
+            let report = archive::validate_categories(&categories);
+            let broken_dir = state::state_dir().join("broken");
+
+            for failure in &report.failed {
+                println!("FAIL {}: {}", failure.path.display(), failure.error);
+
+                let target = format!("{} to {}", failure.path.display(), broken_dir.display());
+                if fix && !ctx.skip("move", target) {
+                    let dest = archive::quarantine(failure, &broken_dir)?;
+                    println!("     moved to {}", dest.display());
+                }
+            }
+
+            println!("{} passed, {} failed", report.passed, report.failed.len());
+
+            if !report.failed.is_empty() && !fix {
+                return Err(format!(
+                    "{} sounds failed to decode (run with --fix to move them to {})",
+                    report.failed.len(),
+                    broken_dir.display()
+                )
+                .into());
+            }
+        }
+
+        Command::Upgrade { .. }
+        | Command::Changelog { .. }
+        | Command::Rollback { .. }
+        | Command::Archive { .. }
+        | Command::Queue { .. }
+        | Command::Add { .. }
+        | Command::Devices { .. }
+        | Command::Completions { .. }
+        | Command::Blocklist { .. }
+        | Command::Config { .. } => unreachable!(),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Load the archive from every configured root, warning about any that were skipped
+fn load_archive(config: &Config, ctx: &Context) -> Result<SoundArchive, ArchiveError> {
+    let archive = SoundArchive::load(&config.all_sounds_paths(), config.archive_options())?;
+    for warning in &archive.warnings {
+        ctx.warn(warning);
+    }
+    Ok(archive)
+}
+
+/// Check quiet hours and the blocklist for `category` (see
+/// `Config::blocklist_for`) unless `force` is set. During quiet hours
+/// playback is skipped as `quiet_hours_action` says. A blocked play is skipped
+/// with a message, or fails when `require_clear` is set, with `blocked_code` if
+/// nonzero and exit code 2 otherwise.
+fn is_blocked(
+    ctx: &Context,
+    config: &Config,
+    category: Option<&str>,
+    force: bool,
+    require_clear: bool,
+    blocked_code: u8,
+) -> Result<bool, ExitError> {
+    if force {
+        ctx.detail("Blocklist check skipped (--force)");
+        return Ok(false);
+    }
+
+    if let Some(quiet) = config
+        .quiet_hours
+        .filter(|q| q.contains(chrono::Local::now().time()))
+    {
+        let message = format!("quiet hours ({})", quiet);
+        return match config.quiet_hours_action {
+            QuietHoursAction::Silence => Ok(true),
+            QuietHoursAction::Warn => {
+                ctx.say(format_args!("Skipped: {}", message));
+                Ok(true)
+            }
+            QuietHoursAction::Error => {
+                Err(ExitError::new(1, format!("Playback blocked: {}", message)))
+            }
+        };
+    }
+
+    let blocklist = config.blocklist_for(category);
+    #[cfg(feature = "window-detection")]
+    let mut detector = ProcessDetector::new(&blocklist, config.blocklist_mode)
+        .with_window_blocklist(&config.window_blocklist);
+    #[cfg(not(feature = "window-detection"))]
+    let mut detector = ProcessDetector::new(&blocklist, config.blocklist_mode);
+    let result = detector.check_blocklist();
+
+    #[cfg(feature = "window-detection")]
+    let result = match result {
+        SuppressionResult::Clear => detector.check_focused_window(),
+        result => result,
+    };
+
+    match &result {
+        SuppressionResult::Clear => ctx.detail("Blocklist check: clear"),
+        SuppressionResult::Blocked { app_name } => {
+            ctx.detail(format_args!("Blocklist check: {} is running", app_name))
+        }
+    }
+
+    match result {
+        SuppressionResult::Clear => Ok(false),
+        SuppressionResult::Blocked { app_name } if require_clear => Err(ExitError::new(
+            if blocked_code != 0 {
+                blocked_code
+            } else {
+                exit::BLOCKED
+            },
+            format!("Playback blocked: {} is running", app_name),
+        )),
+        SuppressionResult::Blocked { app_name } => {
+            ctx.say(format_args!("Skipped: {} is running", app_name));
+            Ok(true)
+        }
+    }
+}
 
-            let player = Player::new()?;
-            let vol = volume as f32 / 100.0;
-            player.play(&snd.path, vol)?;
+/// Copy a sound to `dest`, keeping its filename when `dest` is a directory
+fn export_sound(snd: &SoundFile, dest: &Path, dry_run: bool, ctx: &Context) -> io::Result<()> {
+    let target = if dest.is_dir() {
+        match snd.path.file_name() {
+            Some(name) => dest.join(name),
+            None => dest.to_path_buf(),
         }
+    } else {
+        dest.to_path_buf()
+    };
 
-        Command::Upgrade { .. } => unreachable!(),
+    let size = fs::metadata(&snd.path)?.len();
+    let details = format!("{}, {}", stats::format_size(size), snd.format.name());
+
+    if dry_run {
+        ctx.say(format_args!(
+            "Would export {} -> {} ({})",
+            snd.path.display(),
+            target.display(),
+            details
+        ));
+    } else {
+        fs::copy(&snd.path, &target)?;
+        ctx.say(format_args!(
+            "Exported {} -> {} ({})",
+            snd.path.display(),
+            target.display(),
+            details
+        ));
     }
 
     Ok(())
 }
 
-fn run_upgrade(check_only: bool) -> Result<(), Box<dyn std::error::Error>> {
-    if check_only {
-        let info = upgrade::check_for_update()?;
+/// How `cue list` filters and prints its results
+#[derive(Clone)]
+struct ListOptions {
+    category_path: bool,
+    group_by_format: bool,
+    sounds_path_prefix: Option<PathBuf>,
+    verbose: bool,
+    quiet: bool,
+    json: bool,
+    pretty: bool,
+}
 
-        if info.has_update {
-            println!("Update available: v{} -> v{}", info.current, info.latest);
-            println!("Run 'cue upgrade' to install");
+fn run_list(
+    archive: &SoundArchive,
+    category: Option<String>,
+    pattern: Option<String>,
+    options: ListOptions,
+    palette: &Palette,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let ListOptions {
+        category_path,
+        group_by_format,
+        sounds_path_prefix,
+        verbose,
+        quiet,
+        json,
+        pretty,
+    } = options;
+
+    if let (None, Some(prefix)) = (&category, &sounds_path_prefix) {
+        let sounds: Vec<_> = archive.sounds_from_root(prefix).collect();
+
+        if json {
+            let entries: Vec<_> = sounds
+                .into_iter()
+                .map(|(cat, sound)| SoundEntry::in_category(cat, sound))
+                .collect();
+            println!("{}", output::to_json(&entries, pretty)?);
+        } else if quiet {
+            for (cat, sound) in sounds {
+                println!("{}/{}", cat.name, sound.name);
+            }
         } else {
-            println!("Already up to date (v{})", info.current);
+            println!("Sounds under '{}':", prefix.display());
+            for (cat, sound) in sounds {
+                println!("  {}/{}", palette.category(&cat.name), sound.name);
+            }
         }
-    } else {
-        println!("Checking for updates...");
+        return Ok(());
+    }
 
-        let info = upgrade::perform_upgrade()?;
+    let Some(cat_name) = category else {
+        if category_path {
+            if json {
+                let entries: Vec<_> = archive.categories.iter().map(CategoryPath::new).collect();
+                println!("{}", output::to_json(&entries, pretty)?);
+            } else {
+                for cat in &archive.categories {
+                    println!("{}", output::canonical(&cat.path).display());
+                }
+            }
+        } else if json {
+            let entries: Vec<_> = archive.categories.iter().map(CategoryEntry::new).collect();
+            println!("{}", output::to_json(&entries, pretty)?);
+        } else if quiet {
+            for cat in &archive.categories {
+                println!("{}", cat.name);
+            }
+        } else {
+            println!("Available categories:");
+            for cat in &archive.categories {
+                let count = cat.sounds.len();
+                let plural = if count == 1 { "sound" } else { "sounds" };
+                println!(
+                    "  {} {}",
+                    palette.category(&cat.display_name),
+                    palette.count(&format!("({} {})", count, plural))
+                );
+                if verbose {
+                    print_metadata(cat, "      ");
+                }
+            }
+        }
+        return Ok(());
+    };
 
-        if info.has_update {
-            println!("Upgraded: v{} -> v{}", info.current, info.latest);
+    let cat = archive
+        .category(&cat_name)
+        .ok_or_else(|| ArchiveError::CategoryNotFound(cat_name.clone()))?;
+
+    if category_path {
+        if json {
+            println!("{}", output::to_json(&CategoryPath::new(cat), pretty)?);
+        } else {
+            println!("{}", output::canonical(&cat.path).display());
+        }
+        return Ok(());
+    }
+
+    let mut sounds = match &pattern {
+        Some(pattern) => cat.sounds_matching(pattern),
+        None => cat.sounds.iter().collect(),
+    };
+
+    if let Some(prefix) = &sounds_path_prefix {
+        let under: Vec<_> = archive
+            .sounds_from_root(prefix)
+            .filter(|(c, _)| c.name == cat.name)
+            .map(|(_, sound)| sound)
+            .collect();
+        sounds.retain(|s| under.iter().any(|u| std::ptr::eq(*u, *s)));
+    }
+
+    if group_by_format {
+        let groups = output::group_by_format(sounds);
+
+        if json {
+            let entries: Vec<_> = groups
+                .into_iter()
+                .map(|(format, sounds)| FormatGroup {
+                    format,
+                    sounds: sounds.iter().map(|s| s.name.as_str()).collect(),
+                })
+                .collect();
+            println!("{}", output::to_json(&entries, pretty)?);
+        } else if quiet {
+            for sound in groups.into_values().flatten() {
+                println!("{}", sound.name);
+            }
         } else {
-            println!("Already up to date (v{})", info.current);
+            println!("Sounds in '{}':", palette.category(&cat.display_name));
+            for (format, sounds) in groups {
+                let names: Vec<&str> = sounds.iter().map(|s| s.name.as_str()).collect();
+                let plural = if names.len() == 1 { "sound" } else { "sounds" };
+                println!(
+                    "  {} {}: {}",
+                    palette.format(format, &format.name().to_uppercase()),
+                    palette.count(&format!("({} {})", names.len(), plural)),
+                    names.join(", ")
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    if json {
+        let entries: Vec<_> = sounds.into_iter().map(SoundEntry::new).collect();
+        println!("{}", output::to_json(&entries, pretty)?);
+    } else if quiet {
+        for sound in sounds {
+            println!("{}", sound.name);
+        }
+    } else {
+        println!("Sounds in '{}':", palette.category(&cat.display_name));
+        if verbose {
+            print_metadata(cat, "  ");
         }
+        for sound in sounds {
+            if verbose {
+                let duration = sound
+                    .duration()
+                    .map(stats::format_duration)
+                    .unwrap_or_else(|| "unknown".to_string());
+                // Pad before coloring so escape codes don't throw off the columns
+                println!(
+                    "  {:<24} {} {}",
+                    sound.name,
+                    palette.format(sound.format, &format!("{:<5}", sound.format.name())),
+                    duration
+                );
+            } else {
+                println!("  {}", sound.name);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Print a category's description and tags from `category.toml`, if it has any
+fn print_metadata(cat: &archive::Category, indent: &str) {
+    if let Some(description) = &cat.metadata.description {
+        println!("{}{}", indent, description);
+    }
+    if !cat.metadata.tags.is_empty() {
+        println!("{}tags: {}", indent, cat.metadata.tags.join(", "));
+    }
+}
+
+/// Prompt on stderr and read a line from stdin, returning `default` for an empty answer
+fn ask(prompt: &str, default: &str) -> io::Result<String> {
+    if default.is_empty() {
+        eprint!("{}: ", prompt);
+    } else {
+        eprint!("{} [{}]: ", prompt, default);
+    }
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim();
+
+    Ok(if answer.is_empty() { default } else { answer }.to_string())
+}
+
+/// Prompt on stderr and read a yes/no answer from stdin.
+/// An empty answer returns `default`; otherwise only an explicit answer
+/// against the default (n/no or y/yes) changes the result.
+fn ask_yes_no(prompt: &str, default: bool) -> io::Result<bool> {
+    eprint!("{}", prompt);
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim().to_lowercase();
+
+    Ok(if default {
+        answer != "n" && answer != "no"
+    } else {
+        answer == "y" || answer == "yes"
+    })
+}
+
+fn run_archive(
+    config: &Config,
+    command: ArchiveCommand,
+    ctx: &Context,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        ArchiveCommand::Gc {
+            dry_run,
+            all_empty_dirs,
+        } => {
+            let dry_run = dry_run || ctx.dry_run;
+            let report = archive::collect_garbage(
+                &config.sounds_path_or_default(),
+                all_empty_dirs,
+                dry_run,
+            )?;
+
+            for path in &report.skipped {
+                ctx.warn(format_args!(
+                    "{} has no supported audio files but is not empty; leaving it",
+                    path.display()
+                ));
+            }
+
+            let verb = if dry_run { "Would remove" } else { "Removed" };
+            for path in &report.removed {
+                ctx.say(format_args!("{}: {}", verb, path.display()));
+            }
+
+            if report.removed.is_empty() {
+                ctx.say("Nothing to clean");
+            }
+        }
+
+        ArchiveCommand::Merge {
+            src_path,
+            overwrite,
+            dry_run,
+        } => {
+            let dry_run = dry_run || ctx.dry_run;
+            let source = SoundArchive::load(&[src_path], config.archive_options())?;
+            let report =
+                archive::merge_archive(&source, &config.all_sounds_paths(), overwrite, dry_run)?;
+
+            let prefix = if dry_run { "Would merge" } else { "Merged" };
+            ctx.say(format_args!(
+                "{} {} categories: {} sounds added, {} skipped",
+                prefix, report.categories, report.added, report.skipped
+            ));
+        }
+
+        ArchiveCommand::Snapshot {
+            output_dir,
+            compress,
+        } => {
+            let roots = config.all_sounds_paths();
+            let archive = SoundArchive::load(&roots[..1], config.archive_options())?;
+            if roots.len() > 1 {
+                let skipped: Vec<String> =
+                    roots[1..].iter().map(|r| r.display().to_string()).collect();
+                ctx.warn(format_args!(
+                    "only the first sounds directory is snapshotted; skipping {}",
+                    skipped.join(", ")
+                ));
+            }
+
+            let sounds: usize = archive.categories.iter().map(|c| c.sounds.len()).sum();
+            let target = format!("{} sounds to {}", sounds, output_dir.display());
+            if ctx.skip("snapshot", target) {
+                return Ok(());
+            }
+
+            let report = archive::create_snapshot(&archive, &output_dir, compress)?;
+
+            ctx.say(format_args!(
+                "Snapshot written to {} ({} files, {})",
+                report.location.display(),
+                report.files,
+                stats::format_size(report.total_bytes)
+            ));
+        }
+
+        ArchiveCommand::Restore { snapshot_dir } => {
+            let dest = config.sounds_path_or_default();
+            let target = format!("{} into {}", snapshot_dir.display(), dest.display());
+            if ctx.skip("restore", target) {
+                return Ok(());
+            }
+
+            let report = archive::restore_snapshot(&snapshot_dir, &dest)?;
+
+            ctx.say(format_args!(
+                "Restored {} files to {}",
+                report.files,
+                dest.display()
+            ));
+        }
+
+        ArchiveCommand::ImportM3u {
+            playlist,
+            category,
+            link,
+        } => {
+            if !archive::is_valid_category_name(&category) {
+                return Err(ArchiveError::InvalidName(category).into());
+            }
+            let entries = M3UParser::parse_file(&playlist)?;
+            let files: Vec<PathBuf> = entries.iter().map(|e| e.path.clone()).collect();
+            let dest = config.sounds_path_or_default().join(&category);
+            let mode = if link {
+                ImportMode::Link
+            } else {
+                ImportMode::Copy
+            };
+            let report = archive::import_files(&files, &dest, mode, ctx.dry_run)?;
+
+            for entry in &entries {
+                let name = entry.path.file_name().unwrap_or_default().to_string_lossy();
+                let label = entry
+                    .title
+                    .as_ref()
+                    .map(|title| format!("{} ({})", title, name))
+                    .unwrap_or_else(|| name.to_string());
+
+                if report.imported.contains(&entry.path) && ctx.dry_run {
+                    ctx.say(format_args!("Would import: {}", label));
+                } else if report.imported.contains(&entry.path) {
+                    ctx.say(format_args!("Imported: {}", label));
+                } else if report.existing.contains(&entry.path) {
+                    ctx.say(format_args!(
+                        "Skipped (already in '{}'): {}",
+                        category, label
+                    ));
+                } else if report.unsupported.contains(&entry.path) {
+                    ctx.warn(format_args!("unsupported format: {}", entry.path.display()));
+                } else if report.missing.contains(&entry.path) {
+                    ctx.warn(format_args!("file not found: {}", entry.path.display()));
+                }
+            }
+
+            ctx.say(format_args!(
+                "{} {} of {} playlist entries into '{}'",
+                if ctx.dry_run {
+                    "Would import"
+                } else {
+                    mode.verb()
+                },
+                report.imported.len(),
+                entries.len(),
+                category
+            ));
+        }
+
+        ArchiveCommand::Lint {
+            fix_permissions,
+            fix_permissions_dry_run,
+        } => {
+            let archive = load_archive(config, ctx)?;
+            let fix = if fix_permissions && !ctx.dry_run {
+                PermissionFix::Apply
+            } else if fix_permissions || fix_permissions_dry_run {
+                PermissionFix::DryRun
+            } else {
+                PermissionFix::Report
+            };
+
+            let report = archive::lint_archive(&archive, fix)?;
+
+            if report.is_clean() {
+                ctx.say("No problems found");
+                return Ok(());
+            }
+
+            let label = match fix {
+                PermissionFix::Report => "Unreadable",
+                PermissionFix::DryRun => "Would fix",
+                PermissionFix::Apply => "Fixed",
+            };
+            for path in &report.unreadable {
+                println!("{}: {}", label, path.display());
+            }
+
+            match fix {
+                PermissionFix::Report => {
+                    return Err(format!(
+                        "{} unreadable sound files (run with --fix-permissions to repair)",
+                        report.unreadable.len()
+                    )
+                    .into())
+                }
+                PermissionFix::DryRun => {
+                    ctx.say(format_args!("Would fix {} files", report.fixed.len()))
+                }
+                PermissionFix::Apply => ctx.say(format_args!("Fixed {} files", report.fixed.len())),
+            }
+        }
+
+        ArchiveCommand::WatchNew {
+            path,
+            auto_play,
+            volume,
+        } => {
+            let dir = match (path, &auto_play) {
+                (Some(path), _) => path,
+                (None, Some(category)) => {
+                    let archive = load_archive(config, ctx)?;
+                    archive
+                        .category(category)
+                        .ok_or_else(|| ArchiveError::CategoryNotFound(category.clone()))?
+                        .path
+                        .clone()
+                }
+                (None, None) => unreachable!("clap requires a path or --auto-play"),
+            };
+
+            let player = match auto_play {
+                Some(_) => Some(ctx.player(config.device.as_deref(), config.latency_mode)?),
+                None => None,
+            };
+            let vol = match &auto_play {
+                Some(category) => config.volume_for(category, volume),
+                None => volume as f32 / 100.0,
+            };
+
+            if !ctx.quiet {
+                eprintln!("Watching {} for new sounds (Ctrl-C to stop)", dir.display());
+            }
+            archive::watch_new_files(&dir, |file, format| {
+                let name = file
+                    .file_name()
+                    .unwrap_or(file.as_os_str())
+                    .to_string_lossy();
+                println!("{} ({})", name, format.name());
+
+                if let Some(player) = &player {
+                    if let Err(e) = player.play(file, vol) {
+                        ctx.warn(format_args!("failed to play {}: {}", name, e));
+                    }
+                }
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Print a completion script for `shell`. Category names from the configured archive
+/// are baked in as values for the category arguments; if the config or archive
+/// can't be loaded, the script completes everything but category names.
+fn run_completions(shell: Shell) -> Result<(), Box<dyn std::error::Error>> {
+    let names: Vec<String> = Config::load()
+        .ok()
+        .and_then(|config| {
+            SoundArchive::load(&config.all_sounds_paths(), config.archive_options()).ok()
+        })
+        .map(|archive| {
+            archive
+                .category_names()
+                .into_iter()
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut command = Cli::command();
+    if !names.is_empty() {
+        for subcommand in ["play", "list", "preview"] {
+            command = command.mut_subcommand(subcommand, |c| {
+                c.mut_arg("category", |a| {
+                    a.value_parser(PossibleValuesParser::new(names.clone()))
+                })
+            });
+        }
+    }
+
+    clap_complete::generate(shell, &mut command, "cue", &mut io::stdout());
+    Ok(())
+}
+
+fn run_devices(mark_default: bool, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let default = Player::default_device_name();
+    let devices: Vec<DeviceEntry> = Player::available_devices()
+        .into_iter()
+        .map(|name| DeviceEntry {
+            is_default: default.as_ref() == Some(&name),
+            name,
+        })
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string(&devices)?);
+        return Ok(());
+    }
+
+    if devices.is_empty() {
+        eprintln!("No audio output devices found");
+    }
+
+    for device in &devices {
+        if mark_default && device.is_default {
+            println!("{} (default)", device.name);
+        } else {
+            println!("{}", device.name);
+        }
+    }
+
+    Ok(())
+}
+
+fn run_add(
+    config: &Config,
+    category: &str,
+    paths: &[PathBuf],
+    move_files: bool,
+    ctx: &Context,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !archive::is_valid_category_name(category) {
+        return Err(ArchiveError::InvalidName(category.to_string()).into());
+    }
+
+    // Reject the whole batch up front rather than adding some files and not others
+    if let Some(path) = paths.iter().find(|p| {
+        p.extension()
+            .and_then(|e| e.to_str())
+            .and_then(AudioFormat::from_extension)
+            .is_none()
+    }) {
+        return Err(ArchiveError::InvalidFormat(path.clone()).into());
+    }
+
+    let mode = if move_files {
+        ImportMode::Move
+    } else {
+        ImportMode::Copy
+    };
+    let dest = config.sounds_path_or_default().join(category);
+    let report = archive::import_files(paths, &dest, mode, ctx.dry_run)?;
+
+    let label = if ctx.dry_run { "Would add" } else { "Added" };
+    for path in &report.imported {
+        ctx.say(format_args!("{}: {}", label, path.display()));
+    }
+    for path in &report.existing {
+        if !ctx.quiet {
+            eprintln!("Skipped (already in '{}'): {}", category, path.display());
+        }
+    }
+    for path in &report.missing {
+        ctx.warn(format_args!("file not found: {}", path.display()));
+    }
+
+    let count = report.imported.len();
+    ctx.say(format_args!(
+        "{} {} {} into '{}' ({})",
+        if ctx.dry_run {
+            "Would add"
+        } else {
+            mode.verb()
+        },
+        count,
+        if count == 1 { "sound" } else { "sounds" },
+        category,
+        dest.display()
+    ));
+
+    Ok(())
+}
+
+fn run_rename(
+    archive: &SoundArchive,
+    command: RenameCommand,
+    ctx: &Context,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let renamed = match command {
+        RenameCommand::Category {
+            old,
+            new,
+            overwrite,
+        } => {
+            if !archive::is_valid_category_name(&new) {
+                return Err(ArchiveError::InvalidName(new).into());
+            }
+            let cat = archive
+                .category(&old)
+                .ok_or_else(|| ArchiveError::CategoryNotFound(old.clone()))?;
+
+            // A category merged from several roots has a directory in each of them
+            let moves: Vec<(PathBuf, PathBuf)> = archive
+                .paths
+                .iter()
+                .filter_map(|root| Some((category_dir(root, &cat.name)?, root.join(&new))))
+                .collect();
+
+            for (dir, dest) in &moves {
+                if dest.exists() && !is_same_path(dest, dir) && !overwrite {
+                    return Err(ArchiveError::AlreadyExists(dest.clone()).into());
+                }
+            }
+            if ctx.dry_run {
+                for (dir, dest) in &moves {
+                    ctx.skip("rename", format!("{} -> {}", dir.display(), dest.display()));
+                }
+                return Ok(());
+            }
+
+            let mut renamed = Vec::new();
+            for (dir, dest) in moves {
+                if dest.exists() && !is_same_path(&dest, &dir) {
+                    fs::remove_dir_all(&dest)?;
+                }
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+
+                let from = output::canonical(&dir);
+                fs::rename(&dir, &dest)?;
+                renamed.push((from, output::canonical(&dest)));
+            }
+
+            if Config::rename_alias(&cat.name, &new)? {
+                ctx.say(format_args!(
+                    "Moved alias '{}' to '{}'",
+                    cat.name,
+                    new.to_lowercase()
+                ));
+            }
+            renamed
+        }
+
+        RenameCommand::Sound {
+            category,
+            old,
+            new,
+            overwrite,
+        } => {
+            if !archive::is_plain_name(&new) {
+                return Err(ArchiveError::InvalidName(new).into());
+            }
+            let cat = archive
+                .category(&category)
+                .ok_or_else(|| ArchiveError::CategoryNotFound(category.clone()))?;
+            let snd = cat.sound(&old).ok_or_else(|| {
+                ArchiveError::SoundNotFound(format!("'{}' in category '{}'", old, category))
+            })?;
+
+            // Accept "name.wav" as well as "name"; the original extension is kept either way
+            let new_path = Path::new(&new);
+            let stem = match new_path
+                .extension()
+                .and_then(|e| e.to_str())
+                .and_then(AudioFormat::from_extension)
+            {
+                Some(_) => new_path.file_stem().unwrap_or_default().to_string_lossy(),
+                None => new.as_str().into(),
+            };
+            let ext = snd.path.extension().unwrap_or_default().to_string_lossy();
+            let dest = snd.path.with_file_name(format!("{}.{}", stem, ext));
+
+            // Another file with the same name in any format would shadow this one
+            let existing = cat
+                .sound(&stem)
+                .map(|s| s.path.clone())
+                .or_else(|| dest.exists().then(|| dest.clone()))
+                .filter(|p| !is_same_path(p, &snd.path));
+            if let (Some(existing), false) = (&existing, overwrite) {
+                return Err(ArchiveError::AlreadyExists(existing.clone()).into());
+            }
+            if ctx.skip(
+                "rename",
+                format!("{} -> {}", snd.path.display(), dest.display()),
+            ) {
+                return Ok(());
+            }
+
+            if let Some(existing) = existing.filter(|e| *e != dest) {
+                fs::remove_file(&existing)?;
+            }
+
+            let from = output::canonical(&snd.path);
+            fs::rename(&snd.path, &dest)?;
+            vec![(from, output::canonical(&dest))]
+        }
+    };
+
+    for (from, to) in renamed {
+        ctx.say(format_args!(
+            "Renamed {} -> {}",
+            from.display(),
+            to.display()
+        ));
+    }
+    Ok(())
+}
+
+/// The directory for `category` under `root`, if it has one. Each path segment
+/// matches case-insensitively, since the loader lowercases category names.
+fn category_dir(root: &Path, category: &str) -> Option<PathBuf> {
+    category
+        .split('/')
+        .try_fold(root.to_path_buf(), |dir, segment| {
+            fs::read_dir(dir)
+                .ok()?
+                .flatten()
+                .map(|entry| entry.path())
+                .find(|path| {
+                    path.is_dir()
+                        && path
+                            .file_name()
+                            .is_some_and(|name| name.to_string_lossy().to_lowercase() == segment)
+                })
+        })
+}
+
+/// Whether two existing paths name the same file, e.g. differing only in case
+/// on a case-insensitive filesystem
+fn is_same_path(a: &Path, b: &Path) -> bool {
+    output::canonical(a) == output::canonical(b)
+}
+
+fn run_queue(
+    config: &Config,
+    command: QueueCommand,
+    ctx: &Context,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        QueueCommand::Add {
+            category,
+            sound,
+            volume,
+        } => {
+            let archive = load_archive(config, ctx)?.with_aliases(&config.aliases);
+            let cat = archive
+                .category(&category)
+                .ok_or_else(|| ArchiveError::CategoryNotFound(category.clone()))?;
+
+            let snd = match &sound {
+                Some(name) => cat.sound(name).ok_or_else(|| {
+                    ArchiveError::SoundNotFound(format!("'{}' in category '{}'", name, category))
+                })?,
+                None => cat
+                    .random()
+                    .ok_or_else(|| ArchiveError::EmptyCategory(category.clone()))?,
+            };
+
+            if ctx.skip("queue", format!("{}/{}", cat.name, snd.name)) {
+                return Ok(());
+            }
+
+            let queued = Queue::update(|queue| {
+                queue.entries.push(QueueEntry {
+                    category: cat.name.clone(),
+                    sound: snd.name.clone(),
+                    path: snd.path.clone(),
+                    volume: config.volume_for(&cat.name, volume),
+                });
+                queue.entries.len()
+            })?;
+
+            ctx.say(format_args!(
+                "Queued {}/{} ({} in queue)",
+                cat.name, snd.name, queued
+            ));
+        }
+
+        QueueCommand::Play { force } => {
+            let queue = Queue::load();
+            if queue.entries.is_empty() {
+                ctx.say("Queue is empty");
+                return Ok(());
+            }
+
+            if is_blocked(ctx, config, None, force, false, 0)? {
+                return Ok(());
+            }
+
+            let player = ctx.player(config.device.as_deref(), config.latency_mode)?;
+            player.play_queue(&queue.playlist())?;
+        }
+
+        QueueCommand::Clear => {
+            if ctx.skip(
+                "clear",
+                format!("{} queued sounds", Queue::load().entries.len()),
+            ) {
+                return Ok(());
+            }
+
+            let count = Queue::update(|queue| queue.entries.drain(..).count())?;
+            ctx.say(format_args!("Cleared {} queued sounds", count));
+        }
+
+        QueueCommand::List => {
+            let queue = Queue::load();
+            if queue.entries.is_empty() {
+                println!("Queue is empty");
+            }
+            for (i, entry) in queue.entries.iter().enumerate() {
+                println!(
+                    "{:>3}. {}/{} ({}%)",
+                    i + 1,
+                    entry.category,
+                    entry.sound,
+                    (entry.volume * 100.0).round()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_blocklist(
+    config: &Config,
+    command: BlocklistCommand,
+    ctx: &Context,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let find = |process: &str| {
+        config
+            .blocklist
+            .iter()
+            .any(|entry| entry.eq_ignore_ascii_case(process))
+    };
+
+    match command {
+        BlocklistCommand::Add { process } => {
+            if find(&process) {
+                ctx.say(format_args!("'{}' is already in the blocklist", process));
+                return Ok(());
+            }
+
+            if ctx.skip("add", format!("'{}' to the blocklist", process)) {
+                return Ok(());
+            }
+
+            Config::edit_blocklist(|list| list.push(process.as_str()))?;
+            ctx.say(format_args!("Added '{}' to the blocklist", process));
+        }
+
+        BlocklistCommand::Remove { process } => {
+            if !find(&process) {
+                return Err(format!("'{}' is not in the blocklist", process).into());
+            }
+
+            if ctx.skip("remove", format!("'{}' from the blocklist", process)) {
+                return Ok(());
+            }
+
+            Config::edit_blocklist(|list| {
+                list.retain(|v| !v.as_str().is_some_and(|e| e.eq_ignore_ascii_case(&process)))
+            })?;
+            ctx.say(format_args!("Removed '{}' from the blocklist", process));
+        }
+
+        BlocklistCommand::List { check } => {
+            if config.blocklist.is_empty() {
+                println!("Blocklist is empty");
+                return Ok(());
+            }
+
+            if !check {
+                for entry in &config.blocklist {
+                    println!("{}", entry);
+                }
+                return Ok(());
+            }
+
+            let mut detector = ProcessDetector::new(&config.blocklist, config.blocklist_mode);
+            for (entry, matched) in detector.matches_per_entry() {
+                match matched {
+                    Some(process) => println!("{:<20} running: {}", entry, process),
+                    None => println!("{:<20} -", entry),
+                }
+            }
+        }
+
+        BlocklistCommand::Clear => {
+            let target = format!("{} entries from the blocklist", config.blocklist.len());
+            if ctx.skip("clear", target) {
+                return Ok(());
+            }
+
+            Config::edit_blocklist(|list| list.clear())?;
+            ctx.say(format_args!(
+                "Cleared {} entries from the blocklist",
+                config.blocklist.len()
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn run_config(command: &ConfigCommand, ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        ConfigCommand::Init { overwrite } => {
+            // Check before prompting so the answers aren't thrown away
+            let path = Config::config_path();
+            if path.exists() && !overwrite {
+                return Err(ConfigError::AlreadyExists(path).into());
+            }
+
+            let default_path = Config::default_sounds_path();
+            let sounds_path = ask("Sounds directory", &default_path.to_string_lossy())?;
+            let default_volume = loop {
+                match ask("Default volume (0-100)", "100")?.parse() {
+                    Ok(volume) if volume <= config::MAX_DEFAULT_VOLUME => break volume,
+                    _ => eprintln!("Please enter a whole number from 0 to 100"),
+                }
+            };
+            let default_category =
+                Some(ask("Default category (blank for none)", "")?).filter(|c| !c.is_empty());
+
+            let options = InitOptions {
+                sounds_path,
+                default_volume,
+                default_category,
+            };
+            if ctx.skip("write", path.display()) {
+                return Ok(());
+            }
+
+            let path = Config::init_file(&options, *overwrite)?;
+            ctx.say(format_args!("Wrote {}", path.display()));
+        }
+
+        ConfigCommand::Show => {
+            let mut config = Config::load()?;
+            config.sounds_path = config.all_sounds_paths();
+            print!("{}", toml::to_string_pretty(&config)?);
+        }
+
+        ConfigCommand::Upgrade { dry_run } => {
+            let dry_run = *dry_run || ctx.dry_run;
+            let report = Config::upgrade_file(dry_run)?;
+
+            if report.applied.is_empty() {
+                ctx.say(format_args!(
+                    "{} is up to date (version {})",
+                    report.path.display(),
+                    report.to_version
+                ));
+                return Ok(());
+            }
+
+            let verb = if dry_run { "Would apply" } else { "Applied" };
+            for description in &report.applied {
+                ctx.say(format_args!("{}: {}", verb, description));
+            }
+
+            let prefix = if dry_run { "Would upgrade" } else { "Upgraded" };
+            ctx.say(format_args!(
+                "{} {} from version {} to {}",
+                prefix,
+                report.path.display(),
+                report.from_version,
+                report.to_version
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn run_upgrade(
+    client: &http::Client,
+    check_only: bool,
+    changelog: bool,
+    options: &UpgradeOptions,
+    ctx: &Context,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if changelog {
+        let info = upgrade::check_for_update(client, options)?;
+
+        if !info.has_update {
+            ctx.say(format_args!("Already up to date (v{})", info.current));
+            return Ok(());
+        }
+
+        println!("Changes in v{}:", info.latest);
+        println!();
+        println!(
+            "{}",
+            info.release_notes
+                .as_deref()
+                .unwrap_or("No release notes available")
+        );
+    } else if check_only {
+        let info = upgrade::check_for_update(client, options)?;
+
+        if info.has_update {
+            ctx.say(format_args!(
+                "Update available: v{} -> v{}",
+                info.current, info.latest
+            ));
+            ctx.say("Run 'cue upgrade' to install");
+        } else {
+            ctx.say(format_args!("Already up to date (v{})", info.current));
+        }
+    } else {
+        ctx.say("Checking for updates...");
+
+        let info = upgrade::perform_upgrade(client, options, |version| {
+            ctx.say(format_args!("Reinstalling v{}...", version))
+        })?;
+
+        if info.has_update && options.dry_run {
+            ctx.say(format_args!(
+                "Would upgrade: v{} -> v{}",
+                info.current, info.latest
+            ));
+        } else if info.has_update {
+            ctx.say(format_args!(
+                "Upgraded: v{} -> v{}",
+                info.current, info.latest
+            ));
+
+            if let Some(notes) = &info.release_notes {
+                ctx.say(format_args!("\n{}", notes));
+            }
+        } else if options.force && options.dry_run {
+            ctx.say(format_args!("Would reinstall: v{}", info.latest));
+        } else if options.force {
+            ctx.say(format_args!("Reinstalled v{}", info.latest));
+        } else {
+            ctx.say(format_args!("Already up to date (v{})", info.current));
+        }
+    }
+
+    Ok(())
+}
+
+fn run_changelog(
+    client: &http::Client,
+    version: Option<&str>,
+    channel: upgrade::Channel,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let notes = upgrade::fetch_release_notes(client, version, channel)?;
+
+    if json {
+        println!("{}", serde_json::to_string(&notes)?);
+        return Ok(());
+    }
+
+    println!("Changes in v{}:", notes.version);
+    println!();
+    match &notes.body {
+        Some(body) => println!("{}", upgrade::render_notes(body)),
+        None => println!("No release notes available"),
+    }
+    println!();
+    println!("{}", notes.url);
+
+    Ok(())
+}
+
+fn run_rollback(
+    to: Option<&str>,
+    options: &UpgradeOptions,
+    ctx: &Context,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(version) = to {
+        let (current, restored) = upgrade::rollback(version, options)?;
+        let verb = if options.dry_run {
+            "Would roll back"
+        } else {
+            "Rolled back"
+        };
+        ctx.say(format_args!("{}: v{} -> v{}", verb, current, restored));
+        return Ok(());
+    }
+
+    let backups = upgrade::list_backups()?;
+
+    if backups.is_empty() {
+        ctx.say(format_args!(
+            "No backups in {}",
+            upgrade::backups_dir().display()
+        ));
+        return Ok(());
+    }
+
+    println!("Available versions:");
+    for backup in &backups {
+        println!("  v{:<12} {}", backup.version, backup.path.display());
+    }
+    ctx.say("\nRun 'cue rollback --to VERSION' to restore one");
+
+    Ok(())
+}
+
+fn run_release_list(
+    client: &http::Client,
+    limit: Option<usize>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let releases = upgrade::list_releases(client, limit)?;
+
+    if releases.is_empty() {
+        println!("No releases found");
+        return Ok(());
+    }
+
+    println!("{:<14} {:<12} PRE-RELEASE", "VERSION", "PUBLISHED");
+    for release in &releases {
+        println!(
+            "{:<14} {:<12} {}",
+            release.tag,
+            release.published.as_deref().unwrap_or("-"),
+            if release.prerelease { "yes" } else { "no" }
+        );
     }
 
     Ok(())
@@ -0,0 +1,163 @@
+use crate::archive::{AudioFormat, Category, SoundFile};
+use crate::search::SearchHit;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// JSON representation of a category in `cue list` output
+#[derive(Serialize)]
+pub struct CategoryEntry<'a> {
+    pub name: &'a str,
+    pub display_name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<&'a str>,
+    pub path: PathBuf,
+    pub sound_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub tags: &'a [String],
+}
+
+impl<'a> CategoryEntry<'a> {
+    pub fn new(cat: &'a Category) -> Self {
+        Self {
+            name: &cat.name,
+            display_name: &cat.display_name,
+            parent: cat.parent.as_deref(),
+            path: canonical(&cat.path),
+            sound_count: cat.sounds.len(),
+            description: cat.metadata.description.as_deref(),
+            tags: &cat.metadata.tags,
+        }
+    }
+}
+
+/// JSON representation of a category's directory for `cue list --category-path`
+#[derive(Serialize)]
+pub struct CategoryPath<'a> {
+    pub name: &'a str,
+    pub path: PathBuf,
+}
+
+impl<'a> CategoryPath<'a> {
+    pub fn new(cat: &'a Category) -> Self {
+        Self {
+            name: &cat.name,
+            path: canonical(&cat.path),
+        }
+    }
+}
+
+/// JSON representation of the sound picked by `cue play --print-path --json`
+#[derive(Serialize)]
+pub struct SoundPath<'a> {
+    pub path: PathBuf,
+    pub name: &'a str,
+    pub category: &'a str,
+    pub format: AudioFormat,
+}
+
+impl<'a> SoundPath<'a> {
+    pub fn new(cat: &'a Category, sound: &'a SoundFile) -> Self {
+        Self {
+            path: canonical(&sound.path),
+            name: &sound.name,
+            category: &cat.name,
+            format: sound.format,
+        }
+    }
+}
+
+/// JSON representation of a sound in `cue list` output
+#[derive(Serialize)]
+pub struct SoundEntry<'a> {
+    /// Owning category, included when listing sounds across categories
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<&'a str>,
+    pub name: &'a str,
+    pub path: PathBuf,
+    pub format: AudioFormat,
+
+    /// Playback length in seconds, or null if it can't be determined
+    pub duration_secs: Option<f64>,
+}
+
+impl<'a> SoundEntry<'a> {
+    pub fn new(sound: &'a SoundFile) -> Self {
+        Self {
+            category: None,
+            name: &sound.name,
+            path: canonical(&sound.path),
+            format: sound.format,
+            duration_secs: sound.duration().map(|d| d.as_secs_f64()),
+        }
+    }
+
+    pub fn in_category(cat: &'a Category, sound: &'a SoundFile) -> Self {
+        Self {
+            category: Some(&cat.name),
+            ..Self::new(sound)
+        }
+    }
+}
+
+/// JSON representation of an audio output device in `cue devices`
+#[derive(Serialize)]
+pub struct DeviceEntry {
+    pub name: String,
+    pub is_default: bool,
+}
+
+/// JSON representation of a `cue search` result
+#[derive(Serialize)]
+pub struct SearchEntry<'a> {
+    pub category: &'a str,
+    pub sound: &'a str,
+    pub path: PathBuf,
+    pub score: f64,
+}
+
+impl<'a> SearchEntry<'a> {
+    pub fn new(hit: &SearchHit<'a>) -> Self {
+        Self {
+            category: &hit.category.name,
+            sound: &hit.sound.name,
+            path: canonical(&hit.sound.path),
+            score: hit.score,
+        }
+    }
+}
+
+/// JSON representation of one format group in `cue list --group-by-format`
+#[derive(Serialize)]
+pub struct FormatGroup<'a> {
+    pub format: AudioFormat,
+    pub sounds: Vec<&'a str>,
+}
+
+/// Group sounds by format, in format order, keeping each group's original order
+pub fn group_by_format<'a>(
+    sounds: impl IntoIterator<Item = &'a SoundFile>,
+) -> BTreeMap<AudioFormat, Vec<&'a SoundFile>> {
+    let mut groups: BTreeMap<AudioFormat, Vec<&SoundFile>> = BTreeMap::new();
+    for sound in sounds {
+        groups.entry(sound.format).or_default().push(sound);
+    }
+    groups
+}
+
+/// Canonicalize a path for display, keeping it as-is if that fails
+pub fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Serialize a value as JSON, indented when `pretty` is set
+pub fn to_json<T: Serialize + ?Sized>(value: &T, pretty: bool) -> serde_json::Result<String> {
+    if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    }
+}
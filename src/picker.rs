@@ -0,0 +1,429 @@
+use crate::archive::{Category, SoundArchive, SoundFile};
+use crate::state;
+use chrono::{Local, NaiveDate};
+use clap::ValueEnum;
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+
+/// How many recent plays per category are remembered for weighting
+const RECENT_LIMIT: usize = 10;
+
+/// Strategy for choosing which sound in a category to play
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum PickStrategy {
+    /// Uniformly random choice
+    #[default]
+    Random,
+
+    /// Cycle through sounds in sorted order
+    Sequential,
+
+    /// Random choice biased against recently played sounds
+    WeightedRecent,
+
+    /// Random order, but every sound plays once before any repeats
+    RoundRobin,
+}
+
+impl PickStrategy {
+    /// Whether this strategy reads and updates persisted state
+    pub fn is_stateful(&self) -> bool {
+        *self != Self::Random
+    }
+
+    /// Pick a sound from the category, updating the selection state
+    pub fn pick<'a>(&self, cat: &'a Category, state: &mut StrategyState) -> Option<&'a SoundFile> {
+        if cat.sounds.is_empty() {
+            return None;
+        }
+
+        let sound = match self {
+            Self::Random => cat.random(),
+            Self::Sequential => {
+                let sequence = state.sequence(cat);
+                let sound = &cat.sounds[sequence.next % cat.sounds.len()];
+                sequence.next = (sequence.next + 1) % cat.sounds.len();
+                Some(sound)
+            }
+            Self::WeightedRecent => {
+                let recent = state.recent.get(&cat.name);
+
+                // Most recent play gets weight 1, older plays progressively more,
+                // and sounds not played recently get the highest weight
+                let weights: Vec<usize> = cat
+                    .sounds
+                    .iter()
+                    .map(|s| {
+                        recent
+                            .and_then(|r| r.iter().rev().position(|n| *n == s.name))
+                            .map(|age| age + 1)
+                            .unwrap_or(RECENT_LIMIT + 1)
+                    })
+                    .collect();
+
+                let dist = WeightedIndex::new(&weights).ok()?;
+                Some(&cat.sounds[dist.sample(&mut thread_rng())])
+            }
+            Self::RoundRobin => {
+                let remaining = state.rounds.entry(cat.name.clone()).or_default();
+                remaining.retain(|name| cat.sound(name).is_some());
+
+                if remaining.is_empty() {
+                    *remaining = cat.sounds.iter().map(|s| s.name.clone()).collect();
+                    remaining.shuffle(&mut thread_rng());
+                }
+
+                remaining.pop().and_then(|name| cat.sound(&name))
+            }
+        }?;
+
+        state.record(&cat.name, &sound.name);
+        Some(sound)
+    }
+}
+
+/// Persisted selection state shared by the stateful pick strategies
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StrategyState {
+    /// Recently played sound names (oldest first), by category
+    #[serde(default)]
+    recent: HashMap<String, Vec<String>>,
+
+    /// Sounds not yet played in the current round-robin cycle, by category
+    #[serde(default)]
+    rounds: HashMap<String, Vec<String>>,
+
+    /// Sequential positions loaded so far, by category (stored in their own files)
+    #[serde(skip)]
+    sequences: HashMap<String, SequentialState>,
+
+    /// Held from `load` until the state is saved or dropped, so concurrent
+    /// runs pick in turn instead of overwriting each other's progress
+    #[serde(skip)]
+    _lock: Option<state::StateLock>,
+}
+
+impl StrategyState {
+    /// Lock and load state from disk, starting fresh if none exists
+    pub fn load() -> io::Result<Self> {
+        let lock = state::lock(&Self::path())?;
+        Ok(Self {
+            _lock: Some(lock),
+            ..state::load_json(&Self::path())
+        })
+    }
+
+    /// Persist state to disk and release the lock
+    pub fn save(self) -> io::Result<()> {
+        for (category, sequence) in &self.sequences {
+            state::save_json(&SequentialState::path(category), sequence)?;
+        }
+        state::save_json(&Self::path(), &self)
+    }
+
+    /// Sequential play position for a category, loaded on first use
+    pub fn sequence(&mut self, cat: &Category) -> &mut SequentialState {
+        self.sequences
+            .entry(cat.name.clone())
+            .or_insert_with(|| SequentialState::load(cat))
+    }
+
+    fn path() -> PathBuf {
+        state::state_dir().join("picker.state")
+    }
+
+    fn record(&mut self, category: &str, sound: &str) {
+        let recent = self.recent.entry(category.to_string()).or_default();
+        recent.retain(|n| n != sound);
+        recent.push(sound.to_string());
+
+        if recent.len() > RECENT_LIMIT {
+            recent.remove(0);
+        }
+    }
+}
+
+/// Picks the category played least often today, so large categories don't dominate.
+/// Plays are divided by each category's `category.toml` weight, so a weight of 2
+/// lets a category play twice as often. Counts are stored in ~/.cue/balance.state
+/// and start over each day.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BalancedCategoryPicker {
+    /// Day the counts belong to
+    date: Option<NaiveDate>,
+
+    /// Plays per category on that day
+    counts: HashMap<String, u32>,
+}
+
+impl BalancedCategoryPicker {
+    /// Load today's play counts, starting fresh on a new day
+    pub fn load() -> Self {
+        let today = Local::now().date_naive();
+        let saved: Self = state::load_json(&Self::path());
+
+        if saved.date == Some(today) {
+            saved
+        } else {
+            Self {
+                date: Some(today),
+                counts: HashMap::new(),
+            }
+        }
+    }
+
+    /// Persist play counts to disk
+    pub fn save(&self) -> io::Result<()> {
+        state::save_json(&Self::path(), self)
+    }
+
+    /// Pick a category under the lock on the saved counts and save the new
+    /// count, so concurrent runs balance against each other's plays
+    pub fn pick_and_save(archive: &SoundArchive) -> io::Result<Option<&Category>> {
+        let _lock = state::lock(&Self::path())?;
+        let mut picker = Self::load();
+        let cat = picker.pick(archive);
+        picker.save()?;
+        Ok(cat)
+    }
+
+    /// Pick the least-played non-empty category, relative to its weight (ties
+    /// broken randomly), and count the play
+    pub fn pick<'a>(&mut self, archive: &'a SoundArchive) -> Option<&'a Category> {
+        let candidates: Vec<&Category> = archive
+            .categories
+            .iter()
+            .filter(|c| !c.sounds.is_empty())
+            .collect();
+
+        let share = |c: &Category| {
+            self.counts.get(&c.name).copied().unwrap_or(0) as f32 / c.metadata.weight
+        };
+        let fewest = candidates.iter().map(|c| share(c)).reduce(f32::min)?;
+        let least_played: Vec<&Category> = candidates
+            .into_iter()
+            .filter(|c| share(c) == fewest)
+            .collect();
+
+        let cat = *least_played.choose(&mut thread_rng())?;
+        *self.counts.entry(cat.name.clone()).or_default() += 1;
+        Some(cat)
+    }
+
+    fn path() -> PathBuf {
+        state::state_dir().join("balance.state")
+    }
+}
+
+/// Position in a category's sequential play order, stored in ~/.cue/seq_<hash>.state
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SequentialState {
+    /// Index of the next sound to play
+    next: usize,
+
+    /// Fingerprint of the category's sounds when the position was saved.
+    /// Adding, removing, or renaming sounds changes it and restarts the sequence.
+    generation: u64,
+}
+
+impl SequentialState {
+    fn load(cat: &Category) -> Self {
+        let generation = generation(cat);
+        let saved: Self = state::load_json(&Self::path(&cat.name));
+
+        if saved.generation == generation {
+            saved
+        } else {
+            Self {
+                next: 0,
+                generation,
+            }
+        }
+    }
+
+    /// Restart the sequence from the first sound
+    pub fn reset(&mut self) {
+        self.next = 0;
+    }
+
+    fn path(category: &str) -> PathBuf {
+        state::state_dir().join(format!("seq_{:016x}.state", state::stable_hash(category)))
+    }
+}
+
+fn generation(cat: &Category) -> u64 {
+    let names: Vec<&str> = cat.sounds.iter().map(|s| s.name.as_str()).collect();
+    state::stable_hash(&names.join("\0"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::ArchiveLoadOptions;
+    use std::fs;
+    use std::path::Path;
+
+    /// An archive under the temp dir with the given sound files, by category
+    fn scratch(name: &str, categories: &[(&str, &[&str])]) -> (PathBuf, SoundArchive) {
+        let root = std::env::temp_dir().join(format!("cue-picker-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (category, files) in categories {
+            fs::create_dir_all(root.join(category)).unwrap();
+            for file in *files {
+                fs::write(root.join(category).join(file), b"").unwrap();
+            }
+        }
+
+        let archive =
+            SoundArchive::load(std::slice::from_ref(&root), ArchiveLoadOptions::default()).unwrap();
+        (root, archive)
+    }
+
+    fn names(sounds: &[&SoundFile]) -> Vec<String> {
+        sounds.iter().map(|s| s.name.clone()).collect()
+    }
+
+    /// Fresh state with the category's sequential position already loaded, so
+    /// nothing is read from the real state directory
+    fn state_for(cat: &Category) -> StrategyState {
+        let mut state = StrategyState::default();
+        state.sequences.insert(
+            cat.name.clone(),
+            SequentialState {
+                next: 0,
+                generation: generation(cat),
+            },
+        );
+        state
+    }
+
+    fn cleanup(root: &Path) {
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn sequential_wraps_around_to_the_first_sound() {
+        let (root, archive) = scratch(
+            "sequential",
+            &[("alerts", &["beep.wav", "bell.wav", "ding.wav"])],
+        );
+        let cat = archive.category("alerts").unwrap();
+        let mut state = state_for(cat);
+
+        let picked: Vec<&SoundFile> = (0..4)
+            .map(|_| PickStrategy::Sequential.pick(cat, &mut state).unwrap())
+            .collect();
+        assert_eq!(names(&picked), ["beep", "bell", "ding", "beep"]);
+
+        cleanup(&root);
+    }
+
+    #[test]
+    fn round_robin_exhausts_a_cycle_before_repeating() {
+        let (root, archive) = scratch(
+            "round-robin",
+            &[("alerts", &["beep.wav", "bell.wav", "ding.wav"])],
+        );
+        let cat = archive.category("alerts").unwrap();
+        let mut state = state_for(cat);
+
+        for _ in 0..3 {
+            let mut cycle = names(
+                &(0..3)
+                    .map(|_| PickStrategy::RoundRobin.pick(cat, &mut state).unwrap())
+                    .collect::<Vec<_>>(),
+            );
+            cycle.sort();
+            assert_eq!(cycle, ["beep", "bell", "ding"]);
+        }
+
+        cleanup(&root);
+    }
+
+    #[test]
+    fn weighted_recent_favours_sounds_not_played_recently() {
+        let (root, archive) = scratch("weighted", &[("alerts", &["beep.wav", "bell.wav"])]);
+        let cat = archive.category("alerts").unwrap();
+
+        // "beep" was just played, so it weighs 1 against "bell"'s RECENT_LIMIT + 1
+        let repeats = (0..1000)
+            .filter(|_| {
+                let mut state = state_for(cat);
+                state
+                    .recent
+                    .insert(cat.name.clone(), vec!["beep".to_string()]);
+                PickStrategy::WeightedRecent
+                    .pick(cat, &mut state)
+                    .unwrap()
+                    .name
+                    == "beep"
+            })
+            .count();
+        assert!(
+            repeats < 250,
+            "recently played sound picked {} times",
+            repeats
+        );
+
+        cleanup(&root);
+    }
+
+    #[test]
+    fn recent_plays_are_capped_and_move_to_the_end() {
+        let mut state = StrategyState::default();
+        for i in 0..RECENT_LIMIT + 5 {
+            state.record("alerts", &format!("sound{}", i));
+        }
+        state.record("alerts", "sound10");
+
+        let recent = &state.recent["alerts"];
+        assert_eq!(recent.len(), RECENT_LIMIT);
+        assert_eq!(recent.first().unwrap(), "sound5");
+        assert_eq!(recent.last().unwrap(), "sound10");
+    }
+
+    #[test]
+    fn empty_categories_pick_nothing() {
+        let (root, archive) = scratch("empty", &[("empty", &[])]);
+        let cat = archive.category("empty").unwrap();
+        let mut state = state_for(cat);
+
+        for strategy in PickStrategy::value_variants() {
+            assert!(strategy.pick(cat, &mut state).is_none());
+        }
+
+        cleanup(&root);
+    }
+
+    #[test]
+    fn balanced_picker_spreads_plays_across_non_empty_categories() {
+        let (root, archive) = scratch(
+            "balanced",
+            &[
+                ("alerts", &["beep.wav", "bell.wav", "ding.wav"]),
+                ("idle", &["hum.wav"]),
+                ("empty", &[]),
+            ],
+        );
+        let mut picker = BalancedCategoryPicker::default();
+
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        for _ in 0..6 {
+            let cat = picker.pick(&archive).unwrap();
+            *counts.entry(cat.name.clone()).or_default() += 1;
+        }
+        assert_eq!(counts.get("alerts"), Some(&3));
+        assert_eq!(counts.get("idle"), Some(&3));
+        assert_eq!(counts.get("empty"), None);
+
+        // A category that has fallen behind is picked until it catches up
+        picker.counts.insert("idle".to_string(), 1);
+        assert_eq!(picker.pick(&archive).unwrap().name, "idle");
+
+        cleanup(&root);
+    }
+}
@@ -0,0 +1,65 @@
+//! PID files that let scripts find and stop a running `cue` process.
+//!
+//! The file is removed when the `PidFile` is dropped. Playback stops on
+//! SIGINT/SIGTERM (see `playback::stop_on_interrupt`) and the process unwinds,
+//! so `kill $(cat /tmp/cue.pid)` leaves nothing behind.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use sysinfo::{Pid, System};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum PidFileError {
+    #[error("Process {pid} from {path} is still running; use --replace-pid-file to take it over")]
+    AlreadyRunning { pid: u32, path: PathBuf },
+
+    #[error("Failed to write PID file: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// A PID file holding this process's ID, removed on drop or termination
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Write the current PID to `path`. Fails if the file names another live
+    /// process, unless `replace` is set; stale files are replaced.
+    pub fn create(path: &Path, replace: bool) -> Result<Self, PidFileError> {
+        if !replace {
+            if let Some(pid) = running_pid(path) {
+                return Err(PidFileError::AlreadyRunning {
+                    pid,
+                    path: path.to_path_buf(),
+                });
+            }
+        }
+
+        fs::write(path, format!("{}\n", process::id()))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// PID recorded in the file, if it names a live process other than this one
+fn running_pid(path: &Path) -> Option<u32> {
+    let pid: u32 = fs::read_to_string(path).ok()?.trim().parse().ok()?;
+    if pid == process::id() {
+        return None;
+    }
+
+    System::new()
+        .refresh_process(Pid::from_u32(pid))
+        .then_some(pid)
+}
@@ -1,56 +1,897 @@
-use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink};
+use crate::duck;
+use crate::visualization;
+use clap::ValueEnum;
+use rodio::cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use rodio::cpal::{self, BufferSize, FromSample, SampleFormat, SizedSample, StreamConfig};
+use rodio::dynamic_mixer::{self, DynamicMixer, DynamicMixerController};
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+use serde::{Deserialize, Serialize};
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{self, BufReader, IsTerminal, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
 use thiserror::Error;
+use tracing::{info, warn};
+
+/// Playback speeds accepted by `PlaybackOptions::speed`
+pub const SPEED_RANGE: std::ops::RangeInclusive<f32> = 0.1..=10.0;
+
+/// How often a playing sink re-checks duck requests from other processes
+const DUCK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often the `--progress` bar is redrawn
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Columns between the brackets of the `--progress` bar
+const PROGRESS_WIDTH: usize = 30;
+
+/// Set by the signal handler from `stop_on_interrupt`
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
 
 #[derive(Debug, Error)]
 pub enum PlaybackError {
     #[error("No audio output device available")]
     NoDevice,
 
+    #[error("No audio output device matching '{0}' (see `cue devices`)")]
+    DeviceNotFound(String),
+
     #[error("Failed to open audio file: {0}")]
     FileError(PathBuf),
 
     #[error("Failed to decode audio: {0}")]
     DecodeError(String),
 
+    #[error("Speed must be between 0.1 and 10.0, got {0}")]
+    InvalidSpeed(f32),
+
     #[error("Playback failed: {0}")]
     StreamError(String),
+
+    #[error("Playback interrupted")]
+    Interrupted,
+}
+
+/// On SIGINT or SIGTERM, stop the sound that's playing and fail any later
+/// playback with `PlaybackError::Interrupted`, so the process unwinds through
+/// its guards (PID file, duck request) instead of exiting from the handler
+pub fn stop_on_interrupt() -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::SeqCst))
+}
+
+/// Whether playback was stopped by a signal (see `stop_on_interrupt`)
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Output buffer sizing, trading latency against glitch resistance
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LatencyMode {
+    /// Small buffer (512 frames) for the quickest start
+    Low,
+
+    /// The device's default buffer size
+    #[default]
+    Balanced,
+
+    /// Large buffer (4096 frames) for stable playback on busy systems
+    High,
+}
+
+impl LatencyMode {
+    /// Fixed buffer size in frames, or `None` for the device default
+    fn buffer_size(&self) -> Option<u32> {
+        match self {
+            Self::Low => Some(512),
+            Self::Balanced => None,
+            Self::High => Some(4096),
+        }
+    }
 }
 
 pub struct Player {
-    _stream: OutputStream,
-    handle: OutputStreamHandle,
+    output: Output,
+
+    /// Name of the device being played to, when it could be read
+    device: Option<String>,
+}
+
+enum Output {
+    /// Stream opened by rodio with the device's default configuration
+    Default {
+        _stream: OutputStream,
+        handle: OutputStreamHandle,
+    },
+
+    /// Stream opened through cpal with a fixed buffer size, fed by a rodio mixer
+    Fixed {
+        _stream: cpal::Stream,
+        mixer: Arc<DynamicMixerController<f32>>,
+    },
+
+    /// No device; each sound is printed instead of played (`cue --dry-run`)
+    DryRun,
 }
 
 impl Player {
     /// Create a new player using the default audio output device
     pub fn new() -> Result<Self, PlaybackError> {
+        let (stream, handle) = OutputStream::try_default().map_err(|_| PlaybackError::NoDevice)?;
+
+        Ok(Self {
+            output: Output::Default {
+                _stream: stream,
+                handle,
+            },
+            device: Self::default_device_name(),
+        })
+    }
+
+    /// Create a player that prints `Would play: PATH at volume V` for each sound
+    /// instead of playing it, finishing immediately
+    pub fn dry_run() -> Self {
+        Self {
+            output: Output::DryRun,
+            device: None,
+        }
+    }
+
+    /// Create a player on the first output device whose name contains `name`
+    /// (case-insensitive)
+    pub fn new_with_device(name: &str) -> Result<Self, PlaybackError> {
+        Self::open(Some(name), LatencyMode::Balanced)
+    }
+
+    /// Create a player on a named output device (or the default one) with the
+    /// latency mode's buffer size, falling back to the device's default buffer size
+    pub fn open(device: Option<&str>, mode: LatencyMode) -> Result<Self, PlaybackError> {
+        let device = match device {
+            Some(name) => find_device(name)?,
+            None if mode == LatencyMode::Balanced => return Self::new(),
+            None => cpal::default_host()
+                .default_output_device()
+                .ok_or(PlaybackError::NoDevice)?,
+        };
+
+        if let Some(frames) = mode.buffer_size() {
+            match Self::new_with_buffer_size(&device, frames) {
+                Ok(player) => return Ok(player),
+                Err(e) => {
+                    warn!(frames, error = %e, "Fixed buffer size rejected");
+                    eprintln!(
+                        "Warning: buffer size of {} frames not supported ({}); using default",
+                        frames, e
+                    );
+                }
+            }
+        }
+
         let (stream, handle) =
-            OutputStream::try_default().map_err(|_| PlaybackError::NoDevice)?;
+            OutputStream::try_from_device(&device).map_err(|_| PlaybackError::NoDevice)?;
+
+        Ok(Self {
+            output: Output::Default {
+                _stream: stream,
+                handle,
+            },
+            device: device.name().ok(),
+        })
+    }
+
+    /// Name of the output device this player opened, if it could be read
+    pub fn device_name(&self) -> Option<&str> {
+        self.device.as_deref()
+    }
+
+    /// Names of every audio output device on the default host
+    pub fn available_devices() -> Vec<String> {
+        cpal::default_host()
+            .output_devices()
+            .map(|devices| devices.filter_map(|d| d.name().ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Name of the system default output device, if there is one
+    pub fn default_device_name() -> Option<String> {
+        cpal::default_host().default_output_device()?.name().ok()
+    }
+
+    fn new_with_buffer_size(device: &cpal::Device, frames: u32) -> Result<Self, PlaybackError> {
+        let supported = device
+            .default_output_config()
+            .map_err(|e| PlaybackError::StreamError(e.to_string()))?;
+
+        let config = StreamConfig {
+            channels: supported.channels(),
+            sample_rate: supported.sample_rate(),
+            buffer_size: BufferSize::Fixed(frames),
+        };
+
+        let (mixer, source) = dynamic_mixer::mixer::<f32>(config.channels, config.sample_rate.0);
+
+        let stream = match supported.sample_format() {
+            SampleFormat::F32 => build_stream::<f32>(device, &config, source),
+            SampleFormat::I16 => build_stream::<i16>(device, &config, source),
+            SampleFormat::U16 => build_stream::<u16>(device, &config, source),
+            format => {
+                return Err(PlaybackError::StreamError(format!(
+                    "unsupported sample format: {}",
+                    format
+                )))
+            }
+        }?;
+
+        stream
+            .play()
+            .map_err(|e| PlaybackError::StreamError(e.to_string()))?;
 
         Ok(Self {
-            _stream: stream,
-            handle,
+            output: Output::Fixed {
+                _stream: stream,
+                mixer,
+            },
+            device: device.name().ok(),
         })
     }
 
     /// Play a sound file, blocking until complete
     pub fn play(&self, path: &Path, volume: f32) -> Result<(), PlaybackError> {
-        let file =
-            File::open(path).map_err(|_| PlaybackError::FileError(path.to_path_buf()))?;
+        self.play_nonblocking(path, volume)?.wait();
+        Ok(())
+    }
 
-        let source = Decoder::new(BufReader::new(file))
-            .map_err(|e| PlaybackError::DecodeError(e.to_string()))?;
+    /// Play a sound file with fades and looping, blocking until complete
+    pub fn play_with_options(
+        &self,
+        path: &Path,
+        opts: PlaybackOptions,
+    ) -> Result<(), PlaybackError> {
+        self.play_measured(path, opts, None).map(|_| ())
+    }
 
-        let sink = Sink::try_new(&self.handle)
-            .map_err(|e| PlaybackError::StreamError(e.to_string()))?;
+    /// Start playing a sound file and return immediately.
+    /// The sound stops if the handle or this player is dropped before it finishes.
+    pub fn play_nonblocking(
+        &self,
+        path: &Path,
+        volume: f32,
+    ) -> Result<PlaybackHandle, PlaybackError> {
+        let opts = PlaybackOptions::new().volume(volume);
+        self.start(path, &opts, Arc::new(OnceLock::new()))
+    }
+
+    /// Play a sound file, stopping early once `limit` has elapsed if given.
+    /// Returns the time from this call until the output pulled the first sample,
+    /// or `None` if no sample was ever pulled.
+    pub fn play_measured(
+        &self,
+        path: &Path,
+        opts: PlaybackOptions,
+        limit: Option<Duration>,
+    ) -> Result<Option<Duration>, PlaybackError> {
+        self.play_repetitions(path, opts, limit, |_| {})
+    }
+
+    /// `play_measured`, calling `on_repetition` with the repetition number
+    /// (from 1) as each repetition of a looped sound starts
+    pub fn play_repetitions(
+        &self,
+        path: &Path,
+        opts: PlaybackOptions,
+        limit: Option<Duration>,
+        mut on_repetition: impl FnMut(u32),
+    ) -> Result<Option<Duration>, PlaybackError> {
+        let called = Instant::now();
+        let deadline = limit.map(|l| called + l);
+        let first_sample = Arc::new(OnceLock::new());
+
+        let times = match opts.loop_count {
+            // Without a gap an endless loop is a single repeating source
+            LoopCount::Forever if opts.gap.is_zero() => {
+                on_repetition(1);
+                self.start(path, &opts, Arc::clone(&first_sample))?
+                    .wait_until(deadline);
+                return Ok(first_sample.get().map(|t| t.duration_since(called)));
+            }
+            LoopCount::Forever => None,
+            LoopCount::Once => Some(1),
+            LoopCount::Times(times) => Some(times),
+        };
+
+        for i in 1.. {
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                break;
+            }
+            on_repetition(i);
+
+            // Fade in on the first repetition and out on the last only
+            let repetition = PlaybackOptions {
+                fade_in: opts.fade_in.filter(|_| i == 1),
+                fade_out: opts.fade_out.filter(|_| times == Some(i)),
+                loop_count: LoopCount::Once,
+                ..opts
+            };
+            self.start(path, &repetition, Arc::clone(&first_sample))?
+                .wait_until(deadline);
+
+            if times.is_some_and(|times| i >= times) {
+                break;
+            }
+            let gap = match deadline {
+                Some(d) => opts.gap.min(d.saturating_duration_since(Instant::now())),
+                None => opts.gap,
+            };
+            thread::sleep(gap);
+        }
+
+        Ok(first_sample.get().map(|t| t.duration_since(called)))
+    }
 
-        sink.set_volume(volume);
-        sink.append(source);
-        sink.sleep_until_end();
+    /// Play several sound files back-to-back on one sink without gaps, each at
+    /// its own volume, blocking until the last finishes
+    pub fn play_queue(&self, sounds: &[(PathBuf, f32)]) -> Result<(), PlaybackError> {
+        let Some((first, _)) = sounds.first() else {
+            return Ok(());
+        };
+
+        if let Output::DryRun = self.output {
+            for (path, volume) in sounds {
+                print_dry_run(path, *volume);
+            }
+            return Ok(());
+        }
+
+        let sink = self.new_sink()?;
+
+        for (path, volume) in sounds {
+            let file =
+                File::open(path).map_err(|_| PlaybackError::FileError(path.to_path_buf()))?;
+            let source = Decoder::new(BufReader::new(file))
+                .map_err(|e| PlaybackError::DecodeError(e.to_string()))?;
+
+            sink.append(source.amplify(*volume));
+            info!(path = %path.display(), volume, "Queued");
+        }
+
+        PlaybackHandle {
+            sink,
+            path: first.clone(),
+            volume: 1.0,
+            fade_out: None,
+            progress: None,
+            follow_ducking: false,
+        }
+        .wait();
 
         Ok(())
     }
+
+    /// Decode a file and start it playing on a new sink, repeating it endlessly for
+    /// `LoopCount::Forever`. Fades are clamped to the sound's length when it's known.
+    fn start(
+        &self,
+        path: &Path,
+        opts: &PlaybackOptions,
+        first_sample: Arc<OnceLock<Instant>>,
+    ) -> Result<PlaybackHandle, PlaybackError> {
+        if !SPEED_RANGE.contains(&opts.speed) {
+            return Err(PlaybackError::InvalidSpeed(opts.speed));
+        }
+        if interrupted() {
+            return Err(PlaybackError::Interrupted);
+        }
+
+        if let Output::DryRun = self.output {
+            print_dry_run(path, opts.volume);
+
+            // An idle sink is already empty, so the handle counts as finished
+            return Ok(PlaybackHandle {
+                sink: Sink::new_idle().0,
+                path: path.to_path_buf(),
+                volume: opts.volume,
+                fade_out: None,
+                progress: None,
+                follow_ducking: false,
+            });
+        }
+
+        let file = File::open(path).map_err(|_| PlaybackError::FileError(path.to_path_buf()))?;
+
+        let source = Decoder::new(BufReader::new(file))
+            .map_err(|e| PlaybackError::DecodeError(e.to_string()))?
+            .speed(opts.speed);
+
+        let total = source.total_duration();
+        let clamp = |fade: Option<Duration>| match (fade, total) {
+            (Some(fade), Some(total)) => Some(fade.min(total)),
+            (fade, _) => fade,
+        };
+        let fade_in = clamp(opts.fade_in).unwrap_or(Duration::ZERO);
+        let fade_out = match (clamp(opts.fade_out), total) {
+            // An endless loop has no final repetition to fade
+            _ if opts.loop_count == LoopCount::Forever => None,
+            (Some(length), Some(end)) => Some(FadeOut { end, length }),
+            (Some(_), None) => {
+                warn!(path = %path.display(), "Sound length unknown; skipping fade-out");
+                None
+            }
+            (None, _) => None,
+        };
+
+        let source: Box<dyn Source<Item = i16> + Send> = match opts.loop_count {
+            LoopCount::Forever => Box::new(source.buffered().repeat_infinite()),
+            _ => Box::new(source),
+        };
+
+        let sink = self.new_sink()?;
+
+        sink.set_volume(opts.volume);
+        sink.append(LatencyMeter::new(source.fade_in(fade_in), first_sample));
+        info!(path = %path.display(), volume = opts.volume, "Playback started");
+
+        Ok(PlaybackHandle {
+            sink,
+            path: path.to_path_buf(),
+            volume: opts.volume,
+            fade_out,
+            progress: (opts.progress && io::stderr().is_terminal()).then(|| Progress {
+                total: total.filter(|_| opts.loop_count != LoopCount::Forever),
+            }),
+            follow_ducking: opts.follow_ducking,
+        })
+    }
+
+    fn new_sink(&self) -> Result<Sink, PlaybackError> {
+        match &self.output {
+            Output::Default { handle, .. } => {
+                Sink::try_new(handle).map_err(|e| PlaybackError::StreamError(e.to_string()))
+            }
+            Output::Fixed { mixer, .. } => {
+                let (sink, queue) = Sink::new_idle();
+                mixer.add(queue);
+                Ok(sink)
+            }
+            Output::DryRun => Ok(Sink::new_idle().0),
+        }
+    }
+}
+
+fn print_dry_run(path: &Path, volume: f32) {
+    println!(
+        "Would play: {} at volume {:.0}%",
+        path.display(),
+        volume * 100.0
+    );
+}
+
+/// Volume, speed, fades, and looping for `Player::play_with_options`
+#[derive(Clone, Copy, Debug)]
+pub struct PlaybackOptions {
+    pub volume: f32,
+
+    /// Playback rate (1.0 = normal). Changes pitch along with tempo, like a
+    /// tape played faster or slower. Must be within 0.1 to 10.0.
+    pub speed: f32,
+
+    /// Ramp up from silence over this long at the start
+    pub fade_in: Option<Duration>,
+
+    /// Ramp down to silence over this long before the end.
+    /// When looping, applies to the final repetition only.
+    pub fade_out: Option<Duration>,
+
+    pub loop_count: LoopCount,
+
+    /// Silence between repetitions when looping
+    pub gap: Duration,
+
+    /// Draw a progress bar on stderr while waiting for the sound to finish.
+    /// Ignored when stderr is not a terminal.
+    pub progress: bool,
+
+    /// Lower the volume while another cue process asks to duck (`--mix-volume`).
+    /// Off by default so playback that can't be ducked never polls for requests.
+    pub follow_ducking: bool,
+}
+
+/// How many times `Player::play_with_options` plays a sound
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LoopCount {
+    #[default]
+    Once,
+    Times(u32),
+
+    /// Repeat until stopped by a time limit or the process ending
+    Forever,
+}
+
+impl From<u32> for LoopCount {
+    /// Convert a `--loop N` value, where 0 means forever
+    fn from(times: u32) -> Self {
+        match times {
+            0 => Self::Forever,
+            1 => Self::Once,
+            n => Self::Times(n),
+        }
+    }
+}
+
+impl Default for PlaybackOptions {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            speed: 1.0,
+            fade_in: None,
+            fade_out: None,
+            loop_count: LoopCount::Once,
+            gap: Duration::ZERO,
+            progress: false,
+            follow_ducking: false,
+        }
+    }
+}
+
+impl PlaybackOptions {
+    /// Full volume with no fades
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn fade_in(mut self, fade: Option<Duration>) -> Self {
+        self.fade_in = fade;
+        self
+    }
+
+    pub fn fade_out(mut self, fade: Option<Duration>) -> Self {
+        self.fade_out = fade;
+        self
+    }
+
+    pub fn loop_count(mut self, loop_count: LoopCount) -> Self {
+        self.loop_count = loop_count;
+        self
+    }
+
+    pub fn gap(mut self, gap: Duration) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    pub fn progress(mut self, progress: bool) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    pub fn follow_ducking(mut self, follow: bool) -> Self {
+        self.follow_ducking = follow;
+        self
+    }
+}
+
+/// A volume ramp to silence over the last `length` of a sound lasting `end`
+#[derive(Clone, Copy, Debug)]
+struct FadeOut {
+    end: Duration,
+    length: Duration,
+}
+
+impl FadeOut {
+    /// Volume factor at playback position `pos`
+    fn factor(&self, pos: Duration) -> f32 {
+        let remaining = self.end.saturating_sub(pos);
+        if remaining >= self.length || self.length.is_zero() {
+            1.0
+        } else {
+            remaining.as_secs_f32() / self.length.as_secs_f32()
+        }
+    }
+}
+
+/// A sound started by `Player::play_nonblocking`. Dropping it stops the sound.
+pub struct PlaybackHandle {
+    sink: Sink,
+    path: PathBuf,
+    volume: f32,
+    fade_out: Option<FadeOut>,
+    progress: Option<Progress>,
+    follow_ducking: bool,
+}
+
+/// A progress bar drawn on stderr by `PlaybackHandle::wait`
+#[derive(Clone, Copy, Debug)]
+struct Progress {
+    /// Length of the sound, or `None` to show a spinner instead of a bar
+    total: Option<Duration>,
+}
+
+impl Progress {
+    fn draw(&self, position: Duration, tick: usize) {
+        let line = match self.total {
+            Some(total) => visualization::progress_bar(position, total, PROGRESS_WIDTH),
+            None => visualization::spinner(position, tick),
+        };
+        eprint!("\r{}", line);
+        let _ = io::stderr().flush();
+    }
+}
+
+impl PlaybackHandle {
+    /// Stop the sound; it can't be resumed afterwards
+    pub fn stop(&self) {
+        self.sink.stop();
+    }
+
+    /// Pause the sound, keeping its position
+    #[allow(dead_code)] // for embedders; the CLI only plays to completion
+    pub fn pause(&self) {
+        self.sink.pause();
+    }
+
+    /// Continue a paused sound
+    #[allow(dead_code)] // for embedders; the CLI only plays to completion
+    pub fn resume(&self) {
+        self.sink.play();
+    }
+
+    /// Change the volume of the sound while it plays (1.0 = 100%)
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume;
+        self.sink.set_volume(volume);
+    }
+
+    /// How far into the sound playback has got
+    pub fn position(&self) -> Duration {
+        self.sink.get_pos()
+    }
+
+    /// Whether the sound has played to the end or been stopped
+    pub fn is_finished(&self) -> bool {
+        self.sink.empty()
+    }
+
+    /// Block until the sound finishes, following duck requests from other processes
+    /// if the sound was started with `follow_ducking`
+    pub fn wait(&self) {
+        self.wait_until(None);
+    }
+
+    /// Block until the sound finishes, `deadline` passes or playback is
+    /// interrupted (see `stop_on_interrupt`), following duck requests
+    /// from other processes and any fade-out by scaling the sink's volume.
+    fn wait_until(&self, deadline: Option<Instant>) {
+        let mut next_duck_check = Instant::now();
+        let mut duck_level = 1.0;
+        let mut next_progress_draw = Instant::now();
+        let mut tick = 0;
+
+        while !self.is_finished() {
+            let now = Instant::now();
+
+            if deadline.is_some_and(|d| now >= d) || interrupted() {
+                self.stop();
+                if self.progress.is_some() {
+                    eprintln!();
+                }
+                info!(path = %self.path.display(), "Playback stopped early");
+                return;
+            }
+
+            if let Some(progress) = self.progress.filter(|_| now >= next_progress_draw) {
+                progress.draw(self.sink.get_pos(), tick);
+                tick += 1;
+                next_progress_draw = now + PROGRESS_INTERVAL;
+            }
+
+            if self.follow_ducking && now >= next_duck_check {
+                duck_level = duck::level();
+                next_duck_check = now + DUCK_POLL_INTERVAL;
+            }
+
+            let fade = self
+                .fade_out
+                .map(|f| f.factor(self.sink.get_pos()))
+                .unwrap_or(1.0);
+            self.sink.set_volume(self.volume * duck_level * fade);
+
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        if let Some(progress) = self.progress {
+            // Leave the bar full rather than at the last 100 ms tick
+            if let Some(total) = progress.total {
+                progress.draw(total, tick);
+            }
+            eprintln!();
+        }
+
+        info!(path = %self.path.display(), "Playback finished");
+    }
+}
+
+/// Source adapter that records when the output first pulls a sample from it
+pub struct LatencyMeter<I> {
+    inner: I,
+    first_sample: Arc<OnceLock<Instant>>,
+}
+
+impl<I> LatencyMeter<I> {
+    pub fn new(inner: I, first_sample: Arc<OnceLock<Instant>>) -> Self {
+        Self {
+            inner,
+            first_sample,
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for LatencyMeter<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next();
+        if item.is_some() && self.first_sample.get().is_none() {
+            let _ = self.first_sample.set(Instant::now());
+        }
+        item
+    }
+}
+
+impl<I> Source for LatencyMeter<I>
+where
+    I: Source,
+    I::Item: rodio::Sample,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+/// Find the first output device whose name contains `name`, case-insensitively
+fn find_device(name: &str) -> Result<cpal::Device, PlaybackError> {
+    let wanted = name.to_lowercase();
+
+    cpal::default_host()
+        .output_devices()
+        .map_err(|e| PlaybackError::StreamError(e.to_string()))?
+        .find(|d| d.name().is_ok_and(|n| n.to_lowercase().contains(&wanted)))
+        .ok_or_else(|| PlaybackError::DeviceNotFound(name.to_string()))
+}
+
+/// Build a cpal output stream that pulls samples from a rodio mixer
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    mut source: DynamicMixer<f32>,
+) -> Result<cpal::Stream, PlaybackError>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _| {
+                for sample in data.iter_mut() {
+                    *sample = T::from_sample(source.next().unwrap_or(0.0));
+                }
+            },
+            |e| warn!(error = %e, "Audio stream error"),
+            None,
+        )
+        .map_err(|e| PlaybackError::StreamError(e.to_string()))
+}
+
+/// Probe a sound file's total duration without playing it.
+/// Returns `None` if the file can't be decoded or the format doesn't report a length.
+pub fn probe_duration(path: &Path) -> Option<Duration> {
+    let file = File::open(path).ok()?;
+    Decoder::new(BufReader::new(file)).ok()?.total_duration()
+}
+
+/// Check that a sound file decodes by decoding up to its first `length`.
+/// Fails if the file can't be opened or decoded, or yields no samples.
+pub fn validate(path: &Path, length: Duration) -> Result<(), PlaybackError> {
+    let file = File::open(path).map_err(|_| PlaybackError::FileError(path.to_path_buf()))?;
+
+    let source = Decoder::new(BufReader::new(file))
+        .map_err(|e| PlaybackError::DecodeError(e.to_string()))?;
+
+    let samples =
+        (length.as_secs_f64() * source.sample_rate() as f64 * source.channels() as f64) as usize;
+    if source.take(samples).count() == 0 {
+        return Err(PlaybackError::DecodeError("no audio data".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Sample encoding for raw PCM written with `write_raw`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum RawFormat {
+    /// Signed 16-bit little-endian
+    Pcm16,
+
+    /// Signed 32-bit little-endian
+    Pcm32,
+
+    /// 32-bit float little-endian
+    Float32,
+}
+
+/// A fully decoded sound
+pub struct DecodedAudio {
+    /// Samples interleaved across channels
+    pub samples: Vec<f32>,
+    pub channels: u16,
+    pub sample_rate: u32,
+}
+
+/// Decode an entire sound file, keeping its channel count and sample rate
+pub fn decode(path: &Path) -> Result<DecodedAudio, PlaybackError> {
+    let file = File::open(path).map_err(|_| PlaybackError::FileError(path.to_path_buf()))?;
+
+    let source = Decoder::new(BufReader::new(file))
+        .map_err(|e| PlaybackError::DecodeError(e.to_string()))?;
+
+    let channels = source.channels();
+    let sample_rate = source.sample_rate();
+
+    Ok(DecodedAudio {
+        samples: source.convert_samples::<f32>().collect(),
+        channels,
+        sample_rate,
+    })
+}
+
+/// Decode an entire sound file into f32 samples (interleaved across channels)
+pub fn decode_samples(path: &Path) -> Result<Vec<f32>, PlaybackError> {
+    Ok(decode(path)?.samples)
+}
+
+/// Write samples as raw little-endian PCM, scaled by `volume` and clipped to full scale
+pub fn write_raw(
+    samples: &[f32],
+    volume: f32,
+    format: RawFormat,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    for &sample in samples {
+        let sample = (sample * volume).clamp(-1.0, 1.0);
+
+        match format {
+            RawFormat::Pcm16 => {
+                out.write_all(&((sample * i16::MAX as f32) as i16).to_le_bytes())?
+            }
+            RawFormat::Pcm32 => {
+                out.write_all(&((sample as f64 * i32::MAX as f64) as i32).to_le_bytes())?
+            }
+            RawFormat::Float32 => out.write_all(&sample.to_le_bytes())?,
+        }
+    }
+
+    out.flush()
 }
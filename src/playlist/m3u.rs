@@ -0,0 +1,136 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A track listed in an M3U playlist
+#[derive(Debug, Clone)]
+pub struct PlaylistEntry {
+    /// Track location, resolved against the playlist's directory
+    pub path: PathBuf,
+
+    /// Display name from a preceding `#EXTINF` line, if any
+    pub title: Option<String>,
+}
+
+/// Parser for plain and extended (`#EXTM3U`) M3U playlists
+pub struct M3UParser {
+    base_dir: PathBuf,
+}
+
+impl M3UParser {
+    /// Create a parser that resolves relative entries against `base_dir`
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: base_dir.into(),
+        }
+    }
+
+    /// Read and parse a playlist file, resolving entries relative to its directory
+    pub fn parse_file(path: &Path) -> io::Result<Vec<PlaylistEntry>> {
+        let contents = fs::read_to_string(path)?;
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        Ok(Self::new(base_dir).parse(&contents))
+    }
+
+    /// Parse playlist text. Comments are skipped, `#EXTINF` titles attach to the
+    /// next entry, and URLs are ignored since only local files can be imported.
+    pub fn parse(&self, contents: &str) -> Vec<PlaylistEntry> {
+        let mut entries = Vec::new();
+        let mut title = None;
+
+        for line in contents.trim_start_matches('\u{feff}').lines() {
+            let line = line.trim();
+
+            if let Some(info) = line.strip_prefix("#EXTINF:") {
+                // "#EXTINF:<seconds>,<display name>"
+                title = info
+                    .split_once(',')
+                    .map(|(_, name)| name.trim().to_string())
+                    .filter(|name| !name.is_empty());
+                continue;
+            }
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.contains("://") && !line.starts_with("file://") {
+                title = None;
+                continue;
+            }
+
+            let location = match line.strip_prefix("file://") {
+                Some(url) => file_url_path(url),
+                None => line.to_string(),
+            };
+            entries.push(PlaylistEntry {
+                path: self.base_dir.join(location),
+                title: title.take(),
+            });
+        }
+
+        entries
+    }
+}
+
+/// The path in a `file://` URL (given without the scheme), with an optional
+/// `localhost` host dropped and `%XX` escapes decoded
+fn file_url_path(url: &str) -> String {
+    let path = url.strip_prefix("localhost").unwrap_or(url);
+
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = tail
+            .get(..2)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (byte, escaped) {
+            (b'%', Some(decoded)) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> Vec<PlaylistEntry> {
+        M3UParser::new("/music").parse(contents)
+    }
+
+    #[test]
+    fn resolves_relative_entries_and_attaches_titles() {
+        let entries = parse("#EXTM3U\n#EXTINF:3,Door bell\nbell.wav\n\n# comment\nsub/beep.mp3\n");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].path, Path::new("/music/bell.wav"));
+        assert_eq!(entries[0].title.as_deref(), Some("Door bell"));
+        assert_eq!(entries[1].path, Path::new("/music/sub/beep.mp3"));
+        assert_eq!(entries[1].title, None);
+    }
+
+    #[test]
+    fn skips_remote_urls_along_with_their_titles() {
+        let entries = parse("#EXTINF:3,Stream\nhttp://example.com/a.mp3\nlocal.wav\n");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, Path::new("/music/local.wav"));
+        assert_eq!(entries[0].title, None);
+    }
+
+    #[test]
+    fn decodes_file_urls() {
+        let entries = parse("file:///sounds/door%20bell.wav\nfile://localhost/sounds/caf%C3%A9.wav\nfile:///100%.wav\n");
+        assert_eq!(entries[0].path, Path::new("/sounds/door bell.wav"));
+        assert_eq!(entries[1].path, Path::new("/sounds/café.wav"));
+        assert_eq!(entries[2].path, Path::new("/100%.wav"));
+    }
+}
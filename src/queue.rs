@@ -0,0 +1,48 @@
+//! Persistent list of sounds for `cue queue`, played back-to-back without gaps.
+
+use crate::state;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::PathBuf;
+
+/// One queued sound
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueEntry {
+    pub category: String,
+    pub sound: String,
+    pub path: PathBuf,
+
+    /// Playback volume (1.0 = 100%)
+    pub volume: f32,
+}
+
+/// Sounds waiting to be played, stored in ~/.cue/queue.json
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Queue {
+    pub entries: Vec<QueueEntry>,
+}
+
+impl Queue {
+    /// Load the queue, starting empty if none exists
+    pub fn load() -> Self {
+        state::load_json(&Self::path())
+    }
+
+    /// Load the queue under its lock, change it with `f`, and save it, so
+    /// concurrent commands don't drop each other's changes
+    pub fn update<R>(f: impl FnOnce(&mut Self) -> R) -> io::Result<R> {
+        state::update_json(&Self::path(), f)
+    }
+
+    /// Paths and volumes in play order, as taken by `Player::play_queue`
+    pub fn playlist(&self) -> Vec<(PathBuf, f32)> {
+        self.entries
+            .iter()
+            .map(|e| (e.path.clone(), e.volume))
+            .collect()
+    }
+
+    fn path() -> PathBuf {
+        state::state_dir().join("queue.json")
+    }
+}
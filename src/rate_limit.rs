@@ -0,0 +1,159 @@
+use crate::state;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Token bucket persisted between invocations
+#[derive(Debug, Serialize, Deserialize)]
+struct Bucket {
+    tokens: f64,
+
+    /// Unix timestamp (seconds) of the last refill
+    last_refill: f64,
+}
+
+impl Bucket {
+    /// Refill for the time since the last refill at `per_minute` tokens a
+    /// minute, then take a token if one is available, otherwise return the time
+    /// until the next one
+    fn take(&mut self, per_minute: u32, now: f64) -> Option<Duration> {
+        let capacity = per_minute as f64;
+        let rate = capacity / 60.0;
+
+        let elapsed = (now - self.last_refill).max(0.0);
+        self.tokens = (self.tokens + elapsed * rate).min(capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+        }
+    }
+}
+
+/// Cross-process token-bucket limiter allowing N plays per minute.
+///
+/// The bucket lives in ~/.cue/rate.state and is guarded by an exclusive
+/// lock on ~/.cue/rate.lock so concurrent invocations share one budget.
+pub struct RateLimiter {
+    per_minute: u32,
+}
+
+impl RateLimiter {
+    pub fn new(per_minute: u32) -> Self {
+        Self { per_minute }
+    }
+
+    /// Take a token, waiting up to `timeout` for one to become available.
+    /// Calls `on_wait` with the expected delay the first time it has to wait.
+    /// Returns false if no token was available before the timeout.
+    pub fn acquire(
+        &self,
+        timeout: Duration,
+        mut on_wait: impl FnMut(Duration),
+    ) -> io::Result<bool> {
+        let deadline = Instant::now() + timeout;
+        let mut waited = false;
+
+        loop {
+            let wait = match self.try_acquire()? {
+                None => return Ok(true),
+                Some(wait) => wait,
+            };
+
+            let now = Instant::now();
+            if now + wait > deadline {
+                return Ok(false);
+            }
+
+            if !waited {
+                on_wait(wait);
+                waited = true;
+            }
+
+            thread::sleep(wait);
+        }
+    }
+
+    /// Take a token if one is available, otherwise return the time until the next one
+    fn try_acquire(&self) -> io::Result<Option<Duration>> {
+        let _lock = state::lock(&Self::path())?;
+
+        let now = unix_now();
+
+        let mut bucket: Bucket =
+            state::load_json::<Option<Bucket>>(&Self::path()).unwrap_or(Bucket {
+                tokens: self.per_minute as f64,
+                last_refill: now,
+            });
+        let result = bucket.take(self.per_minute, now);
+
+        state::save_json(&Self::path(), &bucket)?;
+
+        Ok(result)
+    }
+
+    fn path() -> PathBuf {
+        state::state_dir().join("rate.state")
+    }
+}
+
+fn unix_now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn full(per_minute: u32) -> Bucket {
+        Bucket {
+            tokens: per_minute as f64,
+            last_refill: 1000.0,
+        }
+    }
+
+    #[test]
+    fn empty_bucket_reports_the_wait_for_the_next_token() {
+        let mut bucket = full(2);
+        assert_eq!(bucket.take(2, 1000.0), None);
+        assert_eq!(bucket.take(2, 1000.0), None);
+
+        // Two a minute refills one token every 30 seconds
+        assert_eq!(bucket.take(2, 1000.0), Some(Duration::from_secs(30)));
+        assert_eq!(bucket.take(2, 1010.0), Some(Duration::from_secs(20)));
+    }
+
+    #[test]
+    fn bucket_refills_over_time_up_to_capacity() {
+        let mut bucket = full(6);
+        for _ in 0..6 {
+            assert_eq!(bucket.take(6, 1000.0), None);
+        }
+        assert!(bucket.take(6, 1000.0).is_some());
+
+        // One token every 10 seconds
+        assert_eq!(bucket.take(6, 1010.0), None);
+        assert!(bucket.take(6, 1010.0).is_some());
+
+        // A long idle spell refills no more than a minute's worth
+        for _ in 0..6 {
+            assert_eq!(bucket.take(6, 5000.0), None);
+        }
+        assert!(bucket.take(6, 5000.0).is_some());
+    }
+
+    #[test]
+    fn clock_going_backwards_adds_no_tokens() {
+        let mut bucket = full(1);
+        assert_eq!(bucket.take(1, 1000.0), None);
+        assert!(bucket.take(1, 900.0).is_some());
+    }
+}
@@ -0,0 +1,122 @@
+//! Ranked lookup of sounds by category or sound name for `cue search`.
+
+use crate::archive::{Category, SoundArchive, SoundFile};
+use std::cmp::Ordering;
+
+/// Score for a name that starts with the query
+const PREFIX_SCORE: f64 = 3.0;
+
+/// Score for a name that contains the query elsewhere
+const SUBSTRING_SCORE: f64 = 2.0;
+
+/// A sound matching a search query, with its rank
+pub struct SearchHit<'a> {
+    pub category: &'a Category,
+    pub sound: &'a SoundFile,
+
+    /// Higher is better: 3 for a prefix match, 2 for a substring match, and
+    /// between 0 and 1 for a fuzzy match, by edit distance
+    pub score: f64,
+}
+
+/// Find every sound whose name or category name matches `query`, case-insensitively.
+///
+/// Prefix matches rank first, then substring matches, then (with `fuzzy`) names
+/// within a small edit distance of the query. Ties are ordered by category, then sound.
+pub fn search<'a>(archive: &'a SoundArchive, query: &str, fuzzy: bool) -> Vec<SearchHit<'a>> {
+    let query = query.to_lowercase();
+
+    let mut hits: Vec<SearchHit> = archive
+        .categories
+        .iter()
+        .flat_map(|cat| {
+            let cat_score = match_score(&query, &cat.name, fuzzy)
+                .into_iter()
+                .chain(match_score(&query, cat.leaf_name(), fuzzy))
+                .reduce(f64::max);
+            let query = &query;
+
+            cat.sounds.iter().filter_map(move |sound| {
+                let sound_score = match_score(query, &sound.name.to_lowercase(), fuzzy);
+                let score = sound_score.into_iter().chain(cat_score).reduce(f64::max)?;
+
+                Some(SearchHit {
+                    category: cat,
+                    sound,
+                    score,
+                })
+            })
+        })
+        .collect();
+
+    hits.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.category.name.cmp(&b.category.name))
+            .then_with(|| a.sound.name.cmp(&b.sound.name))
+    });
+
+    hits
+}
+
+/// Score a lowercase name against a lowercase query, or `None` if it doesn't match
+fn match_score(query: &str, name: &str, fuzzy: bool) -> Option<f64> {
+    if name.starts_with(query) {
+        return Some(PREFIX_SCORE);
+    }
+
+    if name.contains(query) {
+        return Some(SUBSTRING_SCORE);
+    }
+
+    if !fuzzy {
+        return None;
+    }
+
+    // Allow roughly one typo per three characters of the query
+    let max_distance = (query.chars().count() / 3).max(1);
+    let distance = edit_distance(query, name);
+
+    (distance <= max_distance).then(|| 1.0 - distance as f64 / (max_distance + 1) as f64)
+}
+
+/// Levenshtein distance between two strings, counted in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_counts_single_edits() {
+        assert_eq!(edit_distance("beep", "beep"), 0);
+        assert_eq!(edit_distance("beep", "bep"), 1);
+        assert_eq!(edit_distance("beep", "beeps"), 1);
+        assert_eq!(edit_distance("beep", "boop"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn edit_distance_handles_empty_and_multibyte_strings() {
+        assert_eq!(edit_distance("", "chime"), 5);
+        assert_eq!(edit_distance("chime", ""), 5);
+        assert_eq!(edit_distance("café", "cafe"), 1);
+    }
+}
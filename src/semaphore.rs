@@ -0,0 +1,56 @@
+use crate::state;
+use fs2::FileExt;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+/// How often a waiting invocation retries for a free slot
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A held slot in an N-slot semaphore shared by all cue processes.
+///
+/// Each slot is a file in ~/.cue/concurrent/ and holding the slot means
+/// holding an exclusive lock on it. The lock is released on drop, and the OS
+/// releases it if the process is killed, so slots never leak.
+pub struct GlobalSemaphore {
+    file: File,
+}
+
+impl GlobalSemaphore {
+    /// Take a free slot out of `slots`, or return `None` if all are in use
+    pub fn try_acquire(slots: u32) -> io::Result<Option<Self>> {
+        let dir = state::state_dir().join("concurrent");
+        fs::create_dir_all(&dir)?;
+
+        for slot in 0..slots {
+            let file = OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(dir.join(format!("slot-{}.lock", slot)))?;
+
+            if file.try_lock_exclusive().is_ok() {
+                return Ok(Some(Self { file }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Block until a slot out of `slots` is free
+    pub fn acquire(slots: u32) -> io::Result<Self> {
+        loop {
+            if let Some(permit) = Self::try_acquire(slots)? {
+                return Ok(permit);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+impl Drop for GlobalSemaphore {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::config::dirs_home;
+use fs2::FileExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Numbers temp files, so saves from one process never share a name
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Directory holding persistent runtime state (~/.cue)
+pub fn state_dir() -> PathBuf {
+    dirs_home().join(".cue")
+}
+
+/// 64-bit hash of `value` that is the same in every build (the first 8 bytes of
+/// its SHA-256), for names of state files and anything else that must survive
+/// a toolchain upgrade. `DefaultHasher` makes no such promise.
+pub fn stable_hash(value: &str) -> u64 {
+    let digest = Sha256::digest(value.as_bytes());
+    u64::from_be_bytes(digest[..8].try_into().expect("SHA-256 is 32 bytes"))
+}
+
+/// Read a JSON state file, falling back to the default if it is missing or unreadable
+pub fn load_json<T: DeserializeOwned + Default>(path: &Path) -> T {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Write a JSON state file atomically (write to a temp file, then rename).
+/// The temp file is unique to this process and save, so concurrent writers
+/// don't rename each other's files away.
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let temp_path = path.with_extension(format!(
+        "{}.{}.tmp",
+        process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&temp_path, serde_json::to_vec(value)?)?;
+    fs::rename(&temp_path, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp_path);
+    })
+}
+
+/// Load a JSON state file under its lock, change it with `f`, and save it
+pub fn update_json<T, R>(path: &Path, f: impl FnOnce(&mut T) -> R) -> io::Result<R>
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    let _lock = lock(path)?;
+    let mut value = load_json(path);
+    let result = f(&mut value);
+    save_json(path, &value)?;
+    Ok(result)
+}
+
+/// Exclusive lock on a state file, so concurrent invocations take turns to
+/// load, change and save it. Released when dropped.
+#[derive(Debug)]
+pub struct StateLock {
+    file: File,
+}
+
+impl Drop for StateLock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+/// Block until this process holds the lock for the state file at `path`
+/// (a `.lock` file beside it)
+pub fn lock(path: &Path) -> io::Result<StateLock> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path.with_extension("lock"))?;
+    file.lock_exclusive()?;
+    Ok(StateLock { file })
+}
@@ -0,0 +1,409 @@
+use crate::archive::{Category, SoundArchive, SoundFile};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+/// Summary of the playback lengths of a set of sounds
+pub struct DurationStats {
+    /// Number of sounds with a known duration
+    pub count: usize,
+
+    /// Number of sounds whose duration could not be determined
+    pub unknown: usize,
+
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    pub median: Duration,
+}
+
+impl DurationStats {
+    /// Compute statistics from probed durations (`None` = unknown length).
+    /// Returns `None` if no duration is known.
+    pub fn from_durations(durations: &[Option<Duration>]) -> Option<Self> {
+        let mut known: Vec<Duration> = durations.iter().flatten().copied().collect();
+        if known.is_empty() {
+            return None;
+        }
+
+        known.sort();
+
+        let count = known.len();
+        let total: Duration = known.iter().sum();
+        let median = if count.is_multiple_of(2) {
+            (known[count / 2 - 1] + known[count / 2]) / 2
+        } else {
+            known[count / 2]
+        };
+
+        Some(Self {
+            count,
+            unknown: durations.len() - count,
+            min: known[0],
+            max: known[count - 1],
+            mean: total / count as u32,
+            median,
+        })
+    }
+}
+
+impl fmt::Display for DurationStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  sounds:  {}", self.count)?;
+        if self.unknown > 0 {
+            writeln!(f, "  unknown: {}", self.unknown)?;
+        }
+        writeln!(f, "  min:     {}", format_duration(self.min))?;
+        writeln!(f, "  max:     {}", format_duration(self.max))?;
+        writeln!(f, "  mean:    {}", format_duration(self.mean))?;
+        write!(f, "  median:  {}", format_duration(self.median))
+    }
+}
+
+/// A sound name paired with its length, for shortest/longest reporting
+#[derive(Serialize)]
+pub struct SoundLength {
+    pub name: String,
+    pub duration_secs: f64,
+}
+
+/// Per-category diagnostic summary including audio metadata
+#[derive(Serialize)]
+pub struct CategorySummary {
+    pub category: String,
+    pub sound_count: usize,
+    pub formats: BTreeMap<&'static str, usize>,
+    pub total_size_bytes: u64,
+    pub average_duration_secs: Option<f64>,
+    pub shortest: Option<SoundLength>,
+    pub longest: Option<SoundLength>,
+}
+
+impl CategorySummary {
+    /// Aggregate format, size, and duration data for every sound in a category
+    pub fn compute(cat: &Category) -> Self {
+        let durations = probe_durations(cat);
+
+        let total_size_bytes = cat.sounds.iter().map(|s| s.size()).sum();
+
+        let known: Vec<(&str, Duration)> = cat
+            .sounds
+            .iter()
+            .zip(&durations)
+            .filter_map(|(s, d)| d.map(|d| (s.name.as_str(), d)))
+            .collect();
+
+        let to_length = |(name, d): &(&str, Duration)| SoundLength {
+            name: name.to_string(),
+            duration_secs: d.as_secs_f64(),
+        };
+
+        Self {
+            category: cat.name.clone(),
+            sound_count: cat.sounds.len(),
+            formats: format_counts(cat),
+            total_size_bytes,
+            average_duration_secs: DurationStats::from_durations(&durations)
+                .map(|s| s.mean.as_secs_f64()),
+            shortest: known.iter().min_by_key(|(_, d)| *d).map(to_length),
+            longest: known.iter().max_by_key(|(_, d)| *d).map(to_length),
+        }
+    }
+}
+
+impl fmt::Display for CategorySummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let formats: Vec<String> = self
+            .formats
+            .iter()
+            .map(|(format, count)| format!("{} ({})", format, count))
+            .collect();
+
+        writeln!(f, "Category: {}", self.category)?;
+        writeln!(f, "  {:<14}{}", "Sounds:", self.sound_count)?;
+        writeln!(f, "  {:<14}{}", "Formats:", formats.join(", "))?;
+        writeln!(
+            f,
+            "  {:<14}{}",
+            "Total size:",
+            format_size(self.total_size_bytes)
+        )?;
+
+        let avg = self
+            .average_duration_secs
+            .map(|s| format!("{:.3}s", s))
+            .unwrap_or_else(|| "unknown".to_string());
+        write!(f, "  {:<14}{}", "Avg duration:", avg)?;
+
+        if let Some(s) = &self.shortest {
+            write!(
+                f,
+                "\n  {:<14}{} ({:.3}s)",
+                "Shortest:", s.name, s.duration_secs
+            )?;
+        }
+        if let Some(l) = &self.longest {
+            write!(
+                f,
+                "\n  {:<14}{} ({:.3}s)",
+                "Longest:", l.name, l.duration_secs
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Whole-archive totals for `cue stats`
+#[derive(Serialize)]
+pub struct ArchiveStats {
+    #[serde(rename = "categories")]
+    pub category_count: usize,
+
+    #[serde(rename = "sounds")]
+    pub sound_count: usize,
+
+    #[serde(rename = "size_bytes")]
+    pub total_size_bytes: u64,
+
+    #[serde(rename = "formats")]
+    pub format_counts: BTreeMap<&'static str, usize>,
+
+    pub largest: Option<SoundSize>,
+    pub smallest: Option<SoundSize>,
+    pub most_sounds: Option<CategorySize>,
+    pub fewest_sounds: Option<CategorySize>,
+}
+
+impl ArchiveStats {
+    /// Totals across the archive; file sizes come from filesystem metadata
+    pub fn new(archive: &SoundArchive) -> Self {
+        let mut stats = Self {
+            category_count: archive.category_count(),
+            sound_count: 0,
+            total_size_bytes: 0,
+            format_counts: BTreeMap::new(),
+            largest: None,
+            smallest: None,
+            most_sounds: None,
+            fewest_sounds: None,
+        };
+
+        let sounds = archive
+            .categories
+            .iter()
+            .flat_map(|c| c.sounds.iter().map(move |s| (c, s)));
+
+        for (cat, sound) in sounds {
+            let size = sound.size();
+            stats.sound_count += 1;
+            stats.total_size_bytes += size;
+            *stats.format_counts.entry(sound.format.name()).or_default() += 1;
+
+            let entry = || SoundSize::new(cat, sound);
+            if stats.largest.as_ref().is_none_or(|l| size > l.size_bytes) {
+                stats.largest = Some(entry());
+            }
+            if stats.smallest.as_ref().is_none_or(|s| size < s.size_bytes) {
+                stats.smallest = Some(entry());
+            }
+        }
+
+        stats.most_sounds = archive
+            .categories
+            .iter()
+            .rev()
+            .max_by_key(|c| c.sounds.len())
+            .map(CategorySize::new);
+        stats.fewest_sounds = archive
+            .categories
+            .iter()
+            .min_by_key(|c| c.sounds.len())
+            .map(CategorySize::new);
+
+        stats
+    }
+}
+
+/// A sound and its file size, for largest/smallest reporting
+#[derive(Serialize)]
+pub struct SoundSize {
+    pub category: String,
+    #[serde(skip)]
+    pub category_display_name: String,
+    pub name: String,
+    pub size_bytes: u64,
+}
+
+impl SoundSize {
+    pub fn new(cat: &Category, sound: &SoundFile) -> Self {
+        Self {
+            category: cat.name.clone(),
+            category_display_name: cat.display_name.clone(),
+            name: sound.name.clone(),
+            size_bytes: sound.size(),
+        }
+    }
+}
+
+/// A category and its sound count, for most/fewest reporting
+#[derive(Serialize)]
+pub struct CategorySize {
+    pub name: String,
+    #[serde(skip)]
+    pub display_name: String,
+    pub sounds: usize,
+}
+
+impl CategorySize {
+    pub fn new(cat: &Category) -> Self {
+        Self {
+            name: cat.name.clone(),
+            display_name: cat.display_name.clone(),
+            sounds: cat.sounds.len(),
+        }
+    }
+}
+
+impl fmt::Display for ArchiveStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Archive:")?;
+        writeln!(f, "  {:<16}{}", "Categories:", self.category_count)?;
+        writeln!(f, "  {:<16}{}", "Sounds:", self.sound_count)?;
+        write!(
+            f,
+            "  {:<16}{}",
+            "Total size:",
+            format_size(self.total_size_bytes)
+        )?;
+
+        if let Some(l) = &self.largest {
+            write!(
+                f,
+                "\n  {:<16}{} in {} ({})",
+                "Largest:",
+                l.name,
+                l.category_display_name,
+                format_size(l.size_bytes)
+            )?;
+        }
+        if let Some(s) = &self.smallest {
+            write!(
+                f,
+                "\n  {:<16}{} in {} ({})",
+                "Smallest:",
+                s.name,
+                s.category_display_name,
+                format_size(s.size_bytes)
+            )?;
+        }
+        if let Some(c) = &self.most_sounds {
+            write!(
+                f,
+                "\n  {:<16}{} ({})",
+                "Most sounds:", c.display_name, c.sounds
+            )?;
+        }
+        if let Some(c) = &self.fewest_sounds {
+            write!(
+                f,
+                "\n  {:<16}{} ({})",
+                "Fewest sounds:", c.display_name, c.sounds
+            )?;
+        }
+
+        if !self.format_counts.is_empty() {
+            write!(f, "\n  Formats:")?;
+            for (format, count) in &self.format_counts {
+                write!(f, "\n    {:<14}{:>6}", format, count)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Lightweight per-play category statistics that need no file probing
+#[derive(Serialize)]
+pub struct PlayStats<'a> {
+    pub category: &'a str,
+    pub total_sounds: usize,
+    pub formats: BTreeMap<&'static str, usize>,
+    pub selected: &'a str,
+
+    /// Seed the pick was made with, for replaying it; always null while
+    /// picks come from the unseeded thread RNG
+    pub seed: Option<u64>,
+}
+
+impl<'a> PlayStats<'a> {
+    pub fn new(cat: &'a Category, selected: &'a str) -> Self {
+        Self {
+            category: &cat.name,
+            total_sounds: cat.sounds.len(),
+            formats: format_counts(cat),
+            selected,
+            seed: None,
+        }
+    }
+}
+
+/// Startup latency of a single play, reported by `cue play --measure-latency`
+#[derive(Serialize)]
+pub struct LatencyReport {
+    /// From the play call until the output pulled the first sample
+    pub time_to_first_sample_ms: Option<f64>,
+
+    /// From process start (config and archive load, player creation) until the first sample
+    pub total_setup_ms: f64,
+}
+
+impl fmt::Display for LatencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let first = self
+            .time_to_first_sample_ms
+            .map(|ms| format!("{:.1} ms", ms))
+            .unwrap_or_else(|| "unknown".to_string());
+        writeln!(f, "Time to first sample: {}", first)?;
+        write!(f, "Total setup time:     {:.1} ms", self.total_setup_ms)
+    }
+}
+
+/// Count the sounds in a category by format
+pub fn format_counts(cat: &Category) -> BTreeMap<&'static str, usize> {
+    let mut counts = BTreeMap::new();
+    for sound in &cat.sounds {
+        *counts.entry(sound.format.name()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Probe the duration of every sound in a category in parallel
+pub fn probe_durations(cat: &Category) -> Vec<Option<Duration>> {
+    cat.sounds.par_iter().map(|s| s.duration()).collect()
+}
+
+/// Format a duration as seconds with millisecond precision (e.g. "1.250s")
+pub fn format_duration(d: Duration) -> String {
+    format!("{:.3}s", d.as_secs_f64())
+}
+
+/// Format a byte count using binary units (e.g. "1.5 MB")
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
@@ -1,4 +1,9 @@
+use chrono::NaiveTime;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
 use sysinfo::System;
+use tracing::{debug, info, warn};
 
 /// Result of checking for blocking applications
 pub enum SuppressionResult {
@@ -9,29 +14,172 @@ pub enum SuppressionResult {
     Blocked { app_name: String },
 }
 
+/// Daily window in which playback is suppressed. `start` after `end` spans
+/// midnight, e.g. 22:00-07:00.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct QuietHours {
+    #[serde(
+        deserialize_with = "deserialize_time",
+        serialize_with = "serialize_time"
+    )]
+    pub start: NaiveTime,
+
+    #[serde(
+        deserialize_with = "deserialize_time",
+        serialize_with = "serialize_time"
+    )]
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    /// Whether `time` falls inside the window (start inclusive, end exclusive)
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl fmt::Display for QuietHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+/// Accept "HH:MM" as well as "HH:MM:SS"
+fn deserialize_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    NaiveTime::parse_from_str(&s, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(&s, "%H:%M:%S"))
+        .map_err(|_| serde::de::Error::custom(format!("invalid time '{}', expected HH:MM", s)))
+}
+
+fn serialize_time<S: Serializer>(time: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&time.format("%H:%M").to_string())
+}
+
+/// What `cue play` does when it's skipped for quiet hours
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuietHoursAction {
+    /// Skip without printing anything
+    #[default]
+    Silence,
+
+    /// Skip with a message
+    Warn,
+
+    /// Fail with exit code 1
+    Error,
+}
+
+/// How blocklist entries are matched against process names (always ignoring case)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlocklistMode {
+    /// The process name contains the entry
+    #[default]
+    Substring,
+
+    /// The process name is exactly the entry
+    Exact,
+
+    /// The entry is a regular expression; entries that don't compile fall
+    /// back to substring matching
+    Regex,
+}
+
+/// A blocklist entry, compiled for its mode
+enum Pattern {
+    Substring(String),
+    Exact(String),
+    Regex(Regex),
+}
+
+impl Pattern {
+    fn new(entry: &str, mode: BlocklistMode) -> Self {
+        match mode {
+            BlocklistMode::Substring => Self::Substring(entry.to_lowercase()),
+            BlocklistMode::Exact => Self::Exact(entry.to_lowercase()),
+            BlocklistMode::Regex => match RegexBuilder::new(entry).case_insensitive(true).build() {
+                Ok(regex) => Self::Regex(regex),
+                Err(e) => {
+                    warn!(pattern = %entry, error = %e, "Invalid blocklist regex; matching as a substring");
+                    Self::Substring(entry.to_lowercase())
+                }
+            },
+        }
+    }
+
+    /// Whether `name` (already lowercased) matches
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            Self::Substring(entry) => name.contains(entry.as_str()),
+            Self::Exact(entry) => name == entry,
+            Self::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
 /// Handles process enumeration and matching
 pub struct ProcessDetector {
     system: System,
+
+    #[cfg_attr(not(feature = "window-detection"), allow(dead_code))]
+    mode: BlocklistMode,
+
+    /// Blocklist entries with their original text, compiled once per detector
+    patterns: Vec<(String, Pattern)>,
+
+    /// Window blocklist entries, compiled once like `patterns`
+    #[cfg(feature = "window-detection")]
+    window_patterns: Vec<(String, Pattern)>,
 }
 
 impl ProcessDetector {
-    /// Create a new detector, initializing the process list
-    pub fn new() -> Self {
+    /// Create a new detector for `blocklist`, initializing the process list
+    pub fn new(blocklist: &[String], mode: BlocklistMode) -> Self {
         Self {
             system: System::new(),
+            mode,
+            patterns: blocklist
+                .iter()
+                .map(|entry| (entry.clone(), Pattern::new(entry, mode)))
+                .collect(),
+            #[cfg(feature = "window-detection")]
+            window_patterns: Vec::new(),
         }
     }
 
+    /// Also match `blocklist` against the focused window (see `check_focused_window`)
+    #[cfg(feature = "window-detection")]
+    pub fn with_window_blocklist(mut self, blocklist: &[String]) -> Self {
+        self.window_patterns = blocklist
+            .iter()
+            .map(|entry| (entry.clone(), Pattern::new(entry, self.mode)))
+            .collect();
+        self
+    }
+
     /// Check if any blocklisted process is running.
     /// Returns the first matching process name, if any.
-    pub fn check_blocklist(&mut self, blocklist: &[String]) -> SuppressionResult {
+    pub fn check_blocklist(&mut self) -> SuppressionResult {
         self.system.refresh_processes();
+        debug!(entries = self.patterns.len(), "Checking blocklist");
 
         for process in self.system.processes().values() {
             let name = process.name().to_lowercase();
 
-            for blocked in blocklist {
-                if name.contains(&blocked.to_lowercase()) {
+            for (blocked, pattern) in &self.patterns {
+                if pattern.matches(&name) {
+                    info!(process = %process.name(), pattern = %blocked, "Playback blocked");
                     return SuppressionResult::Blocked {
                         app_name: process.name().to_string(),
                     };
@@ -41,4 +189,197 @@ impl ProcessDetector {
 
         SuppressionResult::Clear
     }
+
+    /// For each blocklist entry, in order, the first running process it matches
+    pub fn matches_per_entry(&mut self) -> Vec<(&str, Option<String>)> {
+        self.system.refresh_processes();
+        let names: Vec<(String, &str)> = self
+            .system
+            .processes()
+            .values()
+            .map(|p| (p.name().to_lowercase(), p.name()))
+            .collect();
+
+        self.patterns
+            .iter()
+            .map(|(entry, pattern)| {
+                let matched = names
+                    .iter()
+                    .find(|(lower, _)| pattern.matches(lower))
+                    .map(|(_, name)| name.to_string());
+                (entry.as_str(), matched)
+            })
+            .collect()
+    }
+
+    /// Check whether the frontmost window's title or application name matches
+    /// an entry in the window blocklist, e.g. a browser tab titled "Meet - ...".
+    /// Clear when the list is empty or the focused window can't be determined
+    /// (see `focused_window`).
+    #[cfg(feature = "window-detection")]
+    pub fn check_focused_window(&self) -> SuppressionResult {
+        if self.window_patterns.is_empty() {
+            return SuppressionResult::Clear;
+        }
+        let Some(window) = window::focused_window() else {
+            debug!("Focused window unavailable; skipping window blocklist");
+            return SuppressionResult::Clear;
+        };
+        debug!(app = %window.app, title = %window.title, "Checking focused window");
+
+        let app = window.app.to_lowercase();
+        let title = window.title.to_lowercase();
+
+        for (blocked, pattern) in &self.window_patterns {
+            if pattern.matches(&app) || pattern.matches(&title) {
+                info!(app = %window.app, title = %window.title, pattern = %blocked, "Playback blocked");
+                return SuppressionResult::Blocked {
+                    app_name: if window.title.is_empty() {
+                        window.app
+                    } else {
+                        format!("{} ({})", window.app, window.title)
+                    },
+                };
+            }
+        }
+
+        SuppressionResult::Clear
+    }
+}
+
+/// Frontmost window lookup, through the tools each platform already ships
+/// (or commonly has installed) rather than bindings to its windowing APIs
+#[cfg(feature = "window-detection")]
+mod window {
+    use std::process::Command;
+
+    /// The focused window and the application that owns it
+    pub struct FocusedWindow {
+        pub app: String,
+        pub title: String,
+    }
+
+    #[cfg(unix)]
+    fn run(program: &str, args: &[&str]) -> Option<String> {
+        let output = Command::new(program).args(args).output().ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Ask X11 through `xdotool`. Wayland compositors don't expose the focused
+    /// window, so this only sees XWayland windows there, and nothing without $DISPLAY.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn focused_window() -> Option<FocusedWindow> {
+        std::env::var_os("DISPLAY")?;
+
+        let title = run("xdotool", &["getactivewindow", "getwindowname"])?;
+        let app = run("xdotool", &["getactivewindow", "getwindowpid"])
+            .and_then(|pid| pid.parse().ok())
+            .and_then(|pid: usize| {
+                let mut system = sysinfo::System::new();
+                system.refresh_processes();
+                system
+                    .process(sysinfo::Pid::from(pid))
+                    .map(|p| p.name().to_string())
+            })
+            .unwrap_or_default();
+
+        Some(FocusedWindow { app, title })
+    }
+
+    /// Ask System Events through `osascript`. Window titles need the terminal
+    /// to have Accessibility permission; without it only the app name is seen.
+    #[cfg(target_os = "macos")]
+    pub fn focused_window() -> Option<FocusedWindow> {
+        const SCRIPT: &str = r#"
+            tell application "System Events"
+                set frontApp to first application process whose frontmost is true
+                set appName to name of frontApp
+                try
+                    set windowTitle to name of front window of frontApp
+                on error
+                    set windowTitle to ""
+                end try
+            end tell
+            return appName & linefeed & windowTitle
+        "#;
+
+        let output = run("osascript", &["-e", SCRIPT])?;
+        let (app, title) = output.split_once('\n').unwrap_or((&output, ""));
+
+        Some(FocusedWindow {
+            app: app.to_string(),
+            title: title.to_string(),
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn focused_window() -> Option<FocusedWindow> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(s: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(s, "%H:%M").unwrap()
+    }
+
+    fn quiet(start: &str, end: &str) -> QuietHours {
+        QuietHours {
+            start: time(start),
+            end: time(end),
+        }
+    }
+
+    #[test]
+    fn quiet_hours_within_a_day() {
+        let hours = quiet("12:00", "14:00");
+        assert!(hours.contains(time("12:00")));
+        assert!(hours.contains(time("13:30")));
+        assert!(!hours.contains(time("14:00")));
+        assert!(!hours.contains(time("11:59")));
+    }
+
+    #[test]
+    fn quiet_hours_across_midnight() {
+        let hours = quiet("22:00", "07:00");
+        assert!(hours.contains(time("22:00")));
+        assert!(hours.contains(time("23:59")));
+        assert!(hours.contains(time("00:00")));
+        assert!(hours.contains(time("06:59")));
+        assert!(!hours.contains(time("07:00")));
+        assert!(!hours.contains(time("12:00")));
+        assert!(!hours.contains(time("21:59")));
+    }
+
+    #[test]
+    fn substring_and_exact_patterns_ignore_case() {
+        let substring = Pattern::new("Zoom", BlocklistMode::Substring);
+        assert!(substring.matches("zoom.us"));
+        assert!(!substring.matches("slack"));
+
+        let exact = Pattern::new("Zoom", BlocklistMode::Exact);
+        assert!(exact.matches("zoom"));
+        assert!(!exact.matches("zoom.us"));
+    }
+
+    #[test]
+    fn regex_patterns_ignore_case() {
+        let regex = Pattern::new("^OBS(64)?$", BlocklistMode::Regex);
+        assert!(regex.matches("obs"));
+        assert!(regex.matches("obs64"));
+        assert!(!regex.matches("obs-helper"));
+    }
+
+    #[test]
+    fn invalid_regex_falls_back_to_substring() {
+        let pattern = Pattern::new("meet (", BlocklistMode::Regex);
+        assert!(matches!(pattern, Pattern::Substring(_)));
+        assert!(pattern.matches("google meet (chrome)"));
+    }
 }
@@ -0,0 +1,317 @@
+//! Terminal browser for `cue tui`: categories on the left, the selected
+//! category's sounds on the right, and what's playing in a status bar.
+
+use crate::archive::{ArchiveError, Category, SoundArchive, SoundFile};
+use crate::playback::{PlaybackHandle, Player};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How often the status bar refreshes while no keys are pressed
+const TICK: Duration = Duration::from_millis(100);
+
+/// Volume change per `+`/`-` press
+const VOLUME_STEP: f32 = 0.1;
+
+/// Loudest volume the `+` key goes up to
+const MAX_VOLUME: f32 = 2.0;
+
+const HELP: &str =
+    " ↑↓ move  ←→ switch pane  Enter preview  Space random  +/- volume  R reload  Q quit ";
+
+/// Which list the arrow keys move through
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Categories,
+    Sounds,
+}
+
+/// The sound currently playing
+struct NowPlaying {
+    label: String,
+    duration: Option<Duration>,
+    handle: PlaybackHandle,
+}
+
+struct App<F> {
+    archive: SoundArchive,
+    reload: F,
+    player: Player,
+    volume: f32,
+    focus: Pane,
+    categories: ListState,
+    sounds: ListState,
+    playing: Option<NowPlaying>,
+
+    /// Last error or notice, shown in place of the playback status
+    message: Option<String>,
+}
+
+/// Run the browser until the user quits.
+///
+/// `reload` is called when R is pressed to pick up changes to the archive on disk.
+pub fn run<F>(
+    archive: SoundArchive,
+    reload: F,
+    player: Player,
+    volume: f32,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    F: Fn() -> Result<SoundArchive, ArchiveError>,
+{
+    let mut app = App {
+        archive,
+        reload,
+        player,
+        volume,
+        focus: Pane::Categories,
+        categories: ListState::default(),
+        sounds: ListState::default(),
+        playing: None,
+        message: None,
+    };
+    app.reset_selection(None);
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+
+    result.map_err(Into::into)
+}
+
+impl<F> App<F>
+where
+    F: Fn() -> Result<SoundArchive, ArchiveError>,
+{
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            if self
+                .playing
+                .as_ref()
+                .is_some_and(|p| p.handle.is_finished())
+            {
+                self.playing = None;
+            }
+
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if !event::poll(TICK)? {
+                continue;
+            }
+
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Act on a key press, returning `false` when the user asked to quit
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => return false,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Left => self.focus = Pane::Categories,
+            KeyCode::Right | KeyCode::Tab => self.focus = Pane::Sounds,
+            KeyCode::Enter if self.focus == Pane::Categories => self.focus = Pane::Sounds,
+            KeyCode::Enter => {
+                if let (Some(cat), Some(sound)) = (self.selected_category(), self.selected_sound())
+                {
+                    let label = format!("{}/{}", cat.display_name, sound.name);
+                    let path = sound.path.clone();
+                    let duration = sound.duration();
+                    self.start(label, path, duration);
+                }
+            }
+            KeyCode::Char(' ') => {
+                if let Some(cat) = self.selected_category() {
+                    match cat.random() {
+                        Some(sound) => {
+                            let label = format!("{}/{}", cat.display_name, sound.name);
+                            let path = sound.path.clone();
+                            let duration = sound.duration();
+                            self.start(label, path, duration);
+                        }
+                        None => self.message = Some(format!("'{}' has no sounds", cat.name)),
+                    }
+                }
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => self.set_volume(self.volume + VOLUME_STEP),
+            KeyCode::Char('-') => self.set_volume(self.volume - VOLUME_STEP),
+            KeyCode::Char('r') | KeyCode::Char('R') => self.reload(),
+            _ => {}
+        }
+
+        true
+    }
+
+    fn selected_category(&self) -> Option<&Category> {
+        self.categories
+            .selected()
+            .and_then(|i| self.archive.categories.get(i))
+    }
+
+    fn selected_sound(&self) -> Option<&SoundFile> {
+        let cat = self.selected_category()?;
+        self.sounds.selected().and_then(|i| cat.sounds.get(i))
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let (state, len) = match self.focus {
+            Pane::Categories => (&mut self.categories, self.archive.categories.len()),
+            Pane::Sounds => {
+                let len = self.selected_category().map_or(0, |c| c.sounds.len());
+                (&mut self.sounds, len)
+            }
+        };
+
+        if len == 0 {
+            return;
+        }
+
+        let current = state.selected().unwrap_or(0) as isize;
+        state.select(Some((current + delta).clamp(0, len as isize - 1) as usize));
+
+        if self.focus == Pane::Categories {
+            let has_sounds = self
+                .selected_category()
+                .is_some_and(|c| !c.sounds.is_empty());
+            self.sounds.select(has_sounds.then_some(0));
+        }
+    }
+
+    /// Select the category named `keep` if it still exists, or the first one
+    fn reset_selection(&mut self, keep: Option<&str>) {
+        let index = keep
+            .and_then(|name| self.archive.categories.iter().position(|c| c.name == name))
+            .or((!self.archive.categories.is_empty()).then_some(0));
+        self.categories.select(index);
+
+        let has_sounds = self
+            .selected_category()
+            .is_some_and(|c| !c.sounds.is_empty());
+        self.sounds.select(has_sounds.then_some(0));
+    }
+
+    /// Stop whatever is playing and start `path`
+    fn start(&mut self, label: String, path: PathBuf, duration: Option<Duration>) {
+        if let Some(playing) = self.playing.take() {
+            playing.handle.stop();
+        }
+
+        match self.player.play_nonblocking(&path, self.volume) {
+            Ok(handle) => {
+                self.message = None;
+                self.playing = Some(NowPlaying {
+                    label,
+                    duration,
+                    handle,
+                });
+            }
+            Err(e) => self.message = Some(format!("Failed to play {}: {}", label, e)),
+        }
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        // Round so repeated steps don't drift away from whole percentages
+        self.volume = (volume.clamp(0.0, MAX_VOLUME) * 100.0).round() / 100.0;
+
+        if let Some(playing) = &mut self.playing {
+            playing.handle.set_volume(self.volume);
+        }
+    }
+
+    fn reload(&mut self) {
+        let keep = self.selected_category().map(|c| c.name.clone());
+
+        match (self.reload)() {
+            Ok(archive) => {
+                self.archive = archive;
+                self.reset_selection(keep.as_deref());
+                self.message = Some(format!(
+                    "Reloaded {} categories",
+                    self.archive.categories.len()
+                ));
+            }
+            Err(e) => self.message = Some(format!("Reload failed: {}", e)),
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)])
+                .areas(main);
+
+        let highlight = Style::default().add_modifier(Modifier::REVERSED);
+        let pane_block = |title: String, pane: Pane| {
+            let block = Block::bordered().title(title);
+            if self.focus == pane {
+                block.border_style(Style::default().add_modifier(Modifier::BOLD))
+            } else {
+                block
+            }
+        };
+
+        let categories: Vec<ListItem> = self
+            .archive
+            .categories
+            .iter()
+            .map(|c| ListItem::new(format!("{} ({})", c.display_name, c.sounds.len())))
+            .collect();
+        let categories = List::new(categories)
+            .block(pane_block(" Categories ".to_string(), Pane::Categories))
+            .highlight_style(highlight);
+
+        let (title, sounds) = match self.selected_category() {
+            Some(cat) => (
+                format!(" {} ", cat.display_name),
+                cat.sounds
+                    .iter()
+                    .map(|s| ListItem::new(format!("{} ({})", s.name, s.format.name())))
+                    .collect(),
+            ),
+            None => (" Sounds ".to_string(), Vec::new()),
+        };
+        let sounds = List::new(sounds)
+            .block(pane_block(title, Pane::Sounds))
+            .highlight_style(highlight);
+
+        let status_line = Paragraph::new(Line::from(self.status_text()))
+            .block(Block::bordered().title_bottom(HELP));
+
+        frame.render_stateful_widget(categories, left, &mut self.categories);
+        frame.render_stateful_widget(sounds, right, &mut self.sounds);
+        frame.render_widget(status_line, status);
+    }
+
+    fn status_text(&self) -> String {
+        let volume = format!("Volume {:.0}%", self.volume * 100.0);
+
+        if let Some(message) = &self.message {
+            return format!("{}  ·  {}", message, volume);
+        }
+
+        let Some(playing) = &self.playing else {
+            return format!("Stopped  ·  {}", volume);
+        };
+
+        let position = playing.handle.position().as_secs_f64();
+        let progress = match playing.duration {
+            Some(total) => format!("{:.1}s / {:.1}s", position, total.as_secs_f64()),
+            None => format!("{:.1}s", position),
+        };
+
+        format!("▶ {}  {}  ·  {}", playing.label, progress, volume)
+    }
+}
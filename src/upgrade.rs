@@ -1,19 +1,56 @@
+use crate::config::Config;
+use crate::http::Client;
+use crate::stats;
+use crate::visualization;
+use clap::ValueEnum;
+#[cfg(not(windows))]
 use flate2::read::GzDecoder;
 use semver::Version;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::env;
 use std::fs::{self, File};
-use std::io::{self, Read};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+#[cfg(not(windows))]
 use tar::Archive;
 use thiserror::Error;
+use tracing::{debug, info, warn};
 
 const REPO: &str = "shanewwarren/cue";
 const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Release notes shown after an upgrade are cut to this many lines
+const MAX_NOTES_LINES: usize = 20;
+
+/// Releases requested per page when listing all releases
+const RELEASES_PER_PAGE: usize = 100;
+
+/// Tag of the rolling release the nightly channel installs
+const NIGHTLY_TAG: &str = "nightly";
+
+/// Extension of release archives: zip on Windows, gzipped tar elsewhere
+const ARCHIVE_EXTENSION: &str = if cfg!(windows) { "zip" } else { "tar.gz" };
+
+/// Name of the executable inside a release archive
+const BINARY_NAME: &str = if cfg!(windows) { "cue.exe" } else { "cue" };
+
+/// Bytes downloaded between redraws of the download progress bar
+const PROGRESS_STEP: u64 = 512 * 1024;
+
+/// Columns between the brackets of the download progress bar
+const PROGRESS_WIDTH: usize = 30;
+
+/// Release assets listing SHA-256 digests of the tarballs, in `sha256sum` format.
+/// The first one present in a release is used.
+const CHECKSUM_ASSETS: &[&str] = &["checksums.sha256", "SHA256SUMS"];
+
 #[derive(Error, Debug)]
 pub enum UpgradeError {
     #[error("HTTP request failed: {0}")]
-    Http(#[from] ureq::Error),
+    Http(Box<ureq::Error>),
 
     #[error("IO error: {0}")]
     Io(#[from] io::Error),
@@ -27,16 +64,60 @@ pub enum UpgradeError {
     #[error("No release found")]
     NoRelease,
 
+    #[error("No release found for version: {0}")]
+    ReleaseNotFound(String),
+
     #[error("No binary found for platform: {0}")]
     NoBinary(String),
 
     #[error("Could not determine executable path")]
     NoExecutablePath,
+
+    #[error("Self-test of new binary failed: {0}")]
+    SelfTestFailed(String),
+
+    #[error("Checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+
+    #[error("No checksum listed for {0}")]
+    ChecksumNotListed(String),
+
+    #[error("No backup found for version: {0}")]
+    BackupNotFound(String),
+}
+
+impl From<Box<ureq::Error>> for UpgradeError {
+    fn from(e: Box<ureq::Error>) -> Self {
+        Self::Http(e)
+    }
+}
+
+/// Which stream of releases `cue upgrade` follows
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    /// The latest full release
+    #[default]
+    Stable,
+
+    /// The newest release, including pre-releases
+    Beta,
+
+    /// The rolling `nightly` release, whose name carries its version
+    /// (e.g. 0.4.0-nightly.20261015)
+    Nightly,
 }
 
 #[derive(Deserialize)]
 struct Release {
     tag_name: String,
+    name: Option<String>,
+    html_url: String,
+    body: Option<String>,
+    published_at: Option<String>,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
     assets: Vec<Asset>,
 }
 
@@ -46,10 +127,55 @@ struct Asset {
     browser_download_url: String,
 }
 
+/// Options controlling how an upgrade is performed
+#[derive(Debug, Default)]
+pub struct UpgradeOptions {
+    /// Run the new binary with `--version` before replacing the current one
+    pub self_test: bool,
+
+    /// Install this release tag instead of the latest release
+    pub version: Option<String>,
+
+    /// Keep the replaced binary in the backups directory for `cue rollback`
+    pub keep_backup: bool,
+
+    /// How many kept binaries to retain; older versions are pruned (0 keeps all)
+    pub max_backups: usize,
+
+    /// Install the release even if it isn't newer than the current version
+    pub force: bool,
+
+    /// Release stream to take the latest version from when `version` isn't set
+    pub channel: Channel,
+
+    /// Download, verify and self-test as usual, but leave the current binary in place
+    pub dry_run: bool,
+}
+
+impl UpgradeOptions {
+    /// Whether any different version counts as an update, not just a newer one
+    fn is_pinned(&self) -> bool {
+        self.version.is_some() || self.channel == Channel::Nightly
+    }
+}
+
+/// A published release, as shown by `cue upgrade --release-list`
+pub struct ReleaseSummary {
+    pub tag: String,
+
+    /// Publication date (YYYY-MM-DD), if the release has been published
+    pub published: Option<String>,
+
+    pub prerelease: bool,
+}
+
 pub struct UpgradeInfo {
     pub current: Version,
     pub latest: Version,
     pub has_update: bool,
+
+    /// Release notes for the latest release, truncated for display
+    pub release_notes: Option<String>,
 }
 
 fn get_target() -> String {
@@ -65,6 +191,10 @@ fn get_target() -> String {
         "apple-darwin"
     } else if cfg!(target_os = "linux") {
         "unknown-linux-gnu"
+    } else if cfg!(all(target_os = "windows", target_env = "gnu")) {
+        "pc-windows-gnu"
+    } else if cfg!(target_os = "windows") {
+        "pc-windows-msvc"
     } else {
         "unknown"
     };
@@ -72,102 +202,693 @@ fn get_target() -> String {
     format!("{}-{}", arch, os)
 }
 
-fn fetch_latest_release() -> Result<Release, UpgradeError> {
+/// Fetch the newest release on `channel`
+fn fetch_latest_release(client: &Client, channel: Channel) -> Result<Release, UpgradeError> {
+    match channel {
+        Channel::Stable => fetch_latest_stable_release(client),
+        Channel::Beta => fetch_latest_beta_release(client),
+        Channel::Nightly => fetch_release_by_tag(client, NIGHTLY_TAG),
+    }
+}
+
+fn fetch_latest_stable_release(client: &Client) -> Result<Release, UpgradeError> {
     let url = format!("https://api.github.com/repos/{}/releases/latest", REPO);
+    debug!(%url, "Fetching latest release");
 
-    let response: Release = ureq::get(&url)
-        .set("User-Agent", "cue-cli")
-        .call()?
-        .into_json()?;
+    let response: Release = client.get(&url)?.into_json()?;
 
     Ok(response)
 }
 
-pub fn check_for_update() -> Result<UpgradeInfo, UpgradeError> {
-    let release = fetch_latest_release()?;
+/// Fetch the highest-versioned release, pre-releases included, from the most
+/// recent page. The nightly release is left out.
+fn fetch_latest_beta_release(client: &Client) -> Result<Release, UpgradeError> {
+    let url = format!(
+        "https://api.github.com/repos/{}/releases?per_page={}",
+        REPO, RELEASES_PER_PAGE
+    );
+    debug!(%url, "Fetching releases for the beta channel");
+
+    let releases: Vec<Release> = client.get(&url)?.into_json()?;
+
+    releases
+        .into_iter()
+        .filter(|r| r.tag_name != NIGHTLY_TAG)
+        .filter_map(|r| release_version(&r).ok().map(|v| (v, r)))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, release)| release)
+        .ok_or(UpgradeError::NoRelease)
+}
+
+/// Version of a release from its tag (with or without a `v` prefix), falling
+/// back to its name for releases like `nightly` whose tag isn't a version
+fn release_version(release: &Release) -> Result<Version, UpgradeError> {
+    let parse = |s: &str| Version::parse(s.trim_start_matches('v'));
+
+    match (parse(&release.tag_name), &release.name) {
+        (Ok(version), _) => Ok(version),
+        (Err(e), Some(name)) => parse(name).map_err(|_| e.into()),
+        (Err(e), None) => Err(e.into()),
+    }
+}
+
+/// Fetch a specific release by tag, accepting versions with or without the `v` prefix
+fn fetch_release_by_tag(client: &Client, tag: &str) -> Result<Release, UpgradeError> {
+    let mut candidates = vec![tag.to_string()];
+    if !tag.starts_with('v') {
+        candidates.push(format!("v{}", tag));
+    }
+
+    for candidate in &candidates {
+        let url = format!(
+            "https://api.github.com/repos/{}/releases/tags/{}",
+            REPO, candidate
+        );
+        debug!(%url, "Fetching release by tag");
+
+        match client.get(&url) {
+            Ok(response) => return Ok(response.into_json()?),
+            Err(e) if matches!(*e, ureq::Error::Status(404, _)) => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Err(UpgradeError::ReleaseNotFound(tag.to_string()))
+}
+
+/// Fetch the pinned release if one was requested, otherwise the latest
+fn fetch_target_release(
+    client: &Client,
+    options: &UpgradeOptions,
+) -> Result<Release, UpgradeError> {
+    match &options.version {
+        Some(tag) => {
+            // Reject malformed versions before spending a request on them
+            Version::parse(tag.trim_start_matches('v'))?;
+            fetch_release_by_tag(client, tag)
+        }
+        None => fetch_latest_release(client, options.channel),
+    }
+}
+
+/// List published releases, newest first, stopping after `limit` if given
+pub fn list_releases(
+    client: &Client,
+    limit: Option<usize>,
+) -> Result<Vec<ReleaseSummary>, UpgradeError> {
+    let mut releases = Vec::new();
+
+    for page in 1.. {
+        let url = format!(
+            "https://api.github.com/repos/{}/releases?per_page={}&page={}",
+            REPO, RELEASES_PER_PAGE, page
+        );
+        debug!(%url, "Fetching releases");
+
+        let batch: Vec<Release> = client.get(&url)?.into_json()?;
+        let last_page = batch.len() < RELEASES_PER_PAGE;
+
+        releases.extend(batch.into_iter().map(|r| {
+            ReleaseSummary {
+                published: r
+                    .published_at
+                    .map(|d| d.split('T').next().unwrap_or_default().to_string()),
+                tag: r.tag_name,
+                prerelease: r.prerelease,
+            }
+        }));
+
+        if last_page || limit.is_some_and(|n| releases.len() >= n) {
+            break;
+        }
+    }
+
+    if let Some(n) = limit {
+        releases.truncate(n);
+    }
+
+    Ok(releases)
+}
+
+pub fn check_for_update(
+    client: &Client,
+    options: &UpgradeOptions,
+) -> Result<UpgradeInfo, UpgradeError> {
+    let release = fetch_target_release(client, options)?;
 
     let current = Version::parse(CURRENT_VERSION)?;
-    let latest_str = release.tag_name.trim_start_matches('v');
-    let latest = Version::parse(latest_str)?;
+    let latest = release_version(&release)?;
+
+    // A pinned version counts as an update whenever it differs, so downgrades work.
+    // Nightly builds are treated the same, as their pre-release versions sort
+    // below the release they lead up to.
+    let has_update = if options.is_pinned() {
+        latest != current
+    } else {
+        latest > current
+    };
 
     Ok(UpgradeInfo {
-        has_update: latest > current,
+        has_update,
         current,
         latest,
+        release_notes: release_notes(&release),
     })
 }
 
-pub fn perform_upgrade() -> Result<UpgradeInfo, UpgradeError> {
-    let release = fetch_latest_release()?;
+/// Download and install the target release. `on_reinstall` is called with its
+/// version before downloading when `--force` reinstalls a release that isn't newer
+pub fn perform_upgrade(
+    client: &Client,
+    options: &UpgradeOptions,
+    on_reinstall: impl FnOnce(&Version),
+) -> Result<UpgradeInfo, UpgradeError> {
+    let release = fetch_target_release(client, options)?;
 
     let current = Version::parse(CURRENT_VERSION)?;
-    let latest_str = release.tag_name.trim_start_matches('v');
-    let latest = Version::parse(latest_str)?;
+    let latest = release_version(&release)?;
+
+    let up_to_date = if options.is_pinned() {
+        latest == current
+    } else {
+        latest <= current
+    };
 
-    if latest <= current {
+    if up_to_date && !options.force {
+        info!(%current, %latest, "Already up to date");
         return Ok(UpgradeInfo {
             has_update: false,
             current,
             latest,
+            release_notes: release_notes(&release),
         });
     }
+    if up_to_date {
+        on_reinstall(&latest);
+    }
 
     let target = get_target();
-    let asset_name = format!("cue-{}-{}.tar.gz", release.tag_name, target);
+    let asset_name = format!("cue-{}-{}.{}", release.tag_name, target, ARCHIVE_EXTENSION);
 
     let asset = release
         .assets
         .iter()
         .find(|a| a.name == asset_name)
-        .ok_or_else(|| UpgradeError::NoBinary(target))?;
+        .ok_or_else(|| UpgradeError::NoBinary(target.clone()))?;
 
     // Download the tarball
-    let response = ureq::get(&asset.browser_download_url)
-        .set("User-Agent", "cue-cli")
-        .call()?;
+    info!(asset = %asset.name, "Downloading release");
+    let response = client.get(&asset.browser_download_url)?;
+    let total = response
+        .header("Content-Length")
+        .and_then(|len| len.parse().ok());
 
+    let started = Instant::now();
     let mut tarball = Vec::new();
-    response.into_reader().read_to_end(&mut tarball)?;
+    ProgressReader::new(response.into_reader(), total).read_to_end(&mut tarball)?;
+    eprintln!(
+        "Downloaded {} in {:.1}s",
+        stats::format_size(tarball.len() as u64),
+        started.elapsed().as_secs_f64()
+    );
+    verify_checksum(client, &release, &asset.name, &tarball)?;
 
     // Extract the binary
-    let decoder = GzDecoder::new(&tarball[..]);
-    let mut archive = Archive::new(decoder);
+    let current_exe = env::current_exe().map_err(|_| UpgradeError::NoExecutablePath)?;
+    let temp_path = current_exe.with_extension("new");
+
+    if !extract_binary(&tarball, &temp_path)? {
+        return Err(UpgradeError::NoBinary(target));
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&temp_path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    if options.self_test {
+        if let Err(e) = self_test(&temp_path, &latest) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e);
+        }
+    }
+
+    if options.dry_run {
+        fs::remove_file(&temp_path)?;
+        info!(%current, %latest, "Dry run; binary left in place");
+    } else {
+        info!(path = %current_exe.display(), "Replacing binary");
+        replace_current_exe(&current_exe, &temp_path, &current, options)?;
+
+        info!(%current, %latest, reinstall = up_to_date, "Upgrade complete");
+    }
+
+    // A forced reinstall of a version that wasn't newer is not an update
+    Ok(UpgradeInfo {
+        has_update: !up_to_date,
+        current,
+        latest,
+        release_notes: release_notes(&release),
+    })
+}
+
+/// Swap `new_binary` in for the running executable, then move the replaced one
+/// into the backups directory as version `current`, or delete it
+fn replace_current_exe(
+    current_exe: &Path,
+    new_binary: &Path,
+    current: &Version,
+    options: &UpgradeOptions,
+) -> Result<(), UpgradeError> {
+    let backup_path = current_exe.with_extension("old");
+
+    // A running executable can be renamed on every platform, though Windows
+    // won't let it be deleted until the process exits
+    if backup_path.exists() {
+        fs::remove_file(&backup_path)?;
+    }
+    fs::rename(current_exe, &backup_path)?;
+    if let Err(e) = fs::rename(new_binary, current_exe) {
+        // Put the original back; nothing is removed until the swap succeeds
+        fs::rename(&backup_path, current_exe)?;
+        return Err(e.into());
+    }
+
+    if options.keep_backup {
+        let stored = store_backup(&backup_path, current)?;
+        info!(path = %stored.display(), "Kept previous binary");
+        prune_backups(options.max_backups)?;
+    } else {
+        remove_or_defer(&backup_path)?;
+    }
+
+    Ok(())
+}
+
+/// Delete a replaced binary, or on Windows, where it can't be deleted while
+/// it's running, leave it for `remove_stale_backup` on the next run
+fn remove_or_defer(backup_path: &Path) -> Result<(), UpgradeError> {
+    if let Err(e) = fs::remove_file(backup_path) {
+        if !cfg!(windows) {
+            return Err(e.into());
+        }
+        info!(path = %backup_path.display(), "Previous binary still in use; removing it next run");
+        File::create(pending_delete_marker(backup_path))?;
+    }
+
+    Ok(())
+}
+
+/// A previous binary kept by `cue upgrade`, as listed by `cue rollback`
+pub struct Backup {
+    pub version: Version,
+    pub path: PathBuf,
+}
+
+/// Where previous binaries are kept, one `<version>/cue` per version
+pub fn backups_dir() -> PathBuf {
+    Config::data_dir().join("backups")
+}
+
+/// Kept binaries, newest version first
+pub fn list_backups() -> Result<Vec<Backup>, UpgradeError> {
+    let entries = match fs::read_dir(backups_dir()) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut backups = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let Some(version) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| Version::parse(name).ok())
+        else {
+            continue;
+        };
+
+        let path = entry.path().join(BINARY_NAME);
+        if path.is_file() {
+            backups.push(Backup { version, path });
+        }
+    }
+
+    backups.sort_by(|a, b| b.version.cmp(&a.version));
+    Ok(backups)
+}
+
+/// Move `binary` into the backups directory as `version`, returning its new path
+fn store_backup(binary: &Path, version: &Version) -> Result<PathBuf, UpgradeError> {
+    let dir = backups_dir().join(version.to_string());
+    fs::create_dir_all(&dir)?;
+
+    let dest = dir.join(BINARY_NAME);
+    if dest.exists() {
+        fs::remove_file(&dest)?;
+    }
+
+    // Renaming fails when the data directory is on another filesystem
+    if fs::rename(binary, &dest).is_err() {
+        fs::copy(binary, &dest)?;
+        remove_or_defer(binary)?;
+    }
+
+    Ok(dest)
+}
+
+/// Delete all but the `max` newest backups; 0 keeps them all, so the one just
+/// stored is never pruned
+fn prune_backups(max: usize) -> Result<(), UpgradeError> {
+    if max == 0 {
+        return Ok(());
+    }
+
+    for backup in list_backups()?.into_iter().skip(max) {
+        if let Some(dir) = backup.path.parent() {
+            fs::remove_dir_all(dir)?;
+            info!(version = %backup.version, "Pruned old backup");
+        }
+    }
+
+    Ok(())
+}
+
+/// Replace the current executable with the kept binary for `version`.
+/// The replaced binary is itself kept as a backup, so a rollback can be undone.
+/// Returns the version that was replaced and the one restored.
+pub fn rollback(
+    version: &str,
+    options: &UpgradeOptions,
+) -> Result<(Version, Version), UpgradeError> {
+    let current = Version::parse(CURRENT_VERSION)?;
+    let target = Version::parse(version.trim_start_matches('v'))?;
+
+    let backup = list_backups()?
+        .into_iter()
+        .find(|b| b.version == target)
+        .ok_or_else(|| UpgradeError::BackupNotFound(version.to_string()))?;
+
+    if options.dry_run {
+        return Ok((current, target));
+    }
 
     let current_exe = env::current_exe().map_err(|_| UpgradeError::NoExecutablePath)?;
     let temp_path = current_exe.with_extension("new");
 
+    // Copy rather than move so the backup survives if the swap fails
+    fs::copy(&backup.path, &temp_path)?;
+
+    info!(path = %current_exe.display(), %current, %target, "Rolling back");
+    replace_current_exe(&current_exe, &temp_path, &current, options)?;
+
+    Ok((current, target))
+}
+
+/// Write the executable from a release `.tar.gz` to `dest`.
+/// Returns `false` if the archive doesn't contain one.
+#[cfg(not(windows))]
+fn extract_binary(archive: &[u8], dest: &Path) -> Result<bool, UpgradeError> {
+    let mut archive = Archive::new(GzDecoder::new(archive));
+
     for entry in archive.entries()? {
         let mut entry = entry?;
         let path = entry.path()?;
 
-        if path.file_name().map(|n| n == "cue").unwrap_or(false) {
-            let mut file = File::create(&temp_path)?;
-            io::copy(&mut entry, &mut file)?;
+        if path.file_name().is_some_and(|n| n == BINARY_NAME) {
+            io::copy(&mut entry, &mut File::create(dest)?)?;
+            return Ok(true);
+        }
+    }
 
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                fs::set_permissions(&temp_path, fs::Permissions::from_mode(0o755))?;
-            }
+    Ok(false)
+}
 
-            break;
+/// Write the executable from a release `.zip` to `dest`.
+/// Returns `false` if the archive doesn't contain one.
+#[cfg(windows)]
+fn extract_binary(archive: &[u8], dest: &Path) -> Result<bool, UpgradeError> {
+    let mut archive = zip::ZipArchive::new(io::Cursor::new(archive))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let is_binary = entry
+            .enclosed_name()
+            .is_some_and(|p| p.file_name().is_some_and(|n| n == BINARY_NAME));
+
+        if is_binary {
+            io::copy(&mut entry, &mut File::create(dest)?)?;
+            return Ok(true);
         }
     }
 
-    // Replace the current binary
+    Ok(false)
+}
+
+/// Marker left next to a previous binary that Windows wouldn't let us delete
+fn pending_delete_marker(backup_path: &Path) -> PathBuf {
+    backup_path.with_extension("old.delete")
+}
+
+/// Delete a previous binary that an upgrade on Windows couldn't remove while it
+/// was running. Call once at startup; does nothing if there is none.
+#[cfg(windows)]
+pub fn remove_stale_backup() {
+    let Ok(current_exe) = env::current_exe() else {
+        return;
+    };
     let backup_path = current_exe.with_extension("old");
+    let marker = pending_delete_marker(&backup_path);
 
-    // On Unix, we can rename while running
-    if backup_path.exists() {
-        fs::remove_file(&backup_path)?;
+    if !marker.exists() {
+        return;
     }
-    fs::rename(&current_exe, &backup_path)?;
-    fs::rename(&temp_path, &current_exe)?;
-    fs::remove_file(&backup_path)?;
 
-    Ok(UpgradeInfo {
-        has_update: true,
-        current,
-        latest,
+    if !backup_path.exists() || fs::remove_file(&backup_path).is_ok() {
+        let _ = fs::remove_file(&marker);
+        debug!(path = %backup_path.display(), "Removed previous binary");
+    }
+}
+
+/// Reader adapter that draws a download progress bar on stderr every
+/// `PROGRESS_STEP` bytes, or a spinner when the total size is unknown.
+/// Draws nothing when stderr is not a terminal.
+struct ProgressReader<R> {
+    inner: R,
+    total: Option<u64>,
+    done: u64,
+    next_draw: u64,
+    tick: usize,
+    enabled: bool,
+}
+
+impl<R> ProgressReader<R> {
+    fn new(inner: R, total: Option<u64>) -> Self {
+        Self {
+            inner,
+            total,
+            done: 0,
+            next_draw: 0,
+            tick: 0,
+            enabled: io::stderr().is_terminal(),
+        }
+    }
+
+    fn draw(&mut self) {
+        let line = match self.total {
+            Some(total) => visualization::download_bar(self.done, total, PROGRESS_WIDTH),
+            None => visualization::download_spinner(self.done, self.tick),
+        };
+        eprint!("\r{}", line);
+        let _ = io::stderr().flush();
+        self.tick += 1;
+    }
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.done += n as u64;
+
+        if !self.enabled {
+            return Ok(n);
+        }
+
+        if n == 0 {
+            // End of the download: show the final state and end the line
+            self.draw();
+            eprintln!();
+            self.enabled = false;
+        } else if self.done >= self.next_draw {
+            self.draw();
+            self.next_draw = self.done + PROGRESS_STEP;
+        }
+
+        Ok(n)
+    }
+}
+
+/// Check a downloaded asset against the release's checksum file. Releases
+/// published without one are installed unverified, with a warning.
+fn verify_checksum(
+    client: &Client,
+    release: &Release,
+    asset_name: &str,
+    bytes: &[u8],
+) -> Result<(), UpgradeError> {
+    let Some(checksums) = CHECKSUM_ASSETS
+        .iter()
+        .find_map(|name| release.assets.iter().find(|a| a.name == *name))
+    else {
+        warn!(release = %release.tag_name, "Release has no checksum file; skipping verification");
+        return Ok(());
+    };
+
+    info!(asset = %checksums.name, "Downloading checksums");
+    let listing = client.get(&checksums.browser_download_url)?.into_string()?;
+
+    // Lines are `<hex digest>  <file name>`, with `*` before the name for binary mode
+    let expected = listing
+        .lines()
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .find(|(_, name)| name.trim_start().trim_start_matches('*') == asset_name)
+        .map(|(digest, _)| digest.to_lowercase())
+        .ok_or_else(|| UpgradeError::ChecksumNotListed(asset_name.to_string()))?;
+
+    let actual = format!("{:x}", Sha256::digest(bytes));
+    if actual != expected {
+        return Err(UpgradeError::ChecksumMismatch { expected, actual });
+    }
+
+    debug!(asset = %asset_name, "Checksum verified");
+    Ok(())
+}
+
+/// Run the downloaded binary with `--version` and check it reports the expected version
+fn self_test(binary: &Path, expected: &Version) -> Result<(), UpgradeError> {
+    info!(path = %binary.display(), "Running self-test");
+
+    let output = Command::new(binary)
+        .arg("--version")
+        .output()
+        .map_err(|e| UpgradeError::SelfTestFailed(e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(UpgradeError::SelfTestFailed(format!(
+            "exited with {}",
+            output.status
+        )));
+    }
+
+    let reported = String::from_utf8_lossy(&output.stdout);
+    let reported = reported.trim();
+    if reported != format!("cue {}", expected) {
+        return Err(UpgradeError::SelfTestFailed(format!(
+            "expected 'cue {}', got '{}'",
+            expected, reported
+        )));
+    }
+
+    Ok(())
+}
+
+/// Extract a release's notes, truncating long notes with a link to the full text
+fn release_notes(release: &Release) -> Option<String> {
+    let body = release.body.as_deref()?.trim();
+    if body.is_empty() {
+        return None;
+    }
+
+    let rendered = render_notes(body);
+    let lines: Vec<&str> = rendered.lines().collect();
+    if lines.len() <= MAX_NOTES_LINES {
+        return Some(rendered);
+    }
+
+    Some(format!(
+        "{}\n(run 'cue changelog' to see full notes)",
+        lines[..MAX_NOTES_LINES].join("\n").trim_end()
+    ))
+}
+
+/// Plain-text form of markdown release notes: headers lose their `#`s and
+/// list items their `-`/`*`/`+` markers, keeping the indentation
+pub fn render_notes(body: &str) -> String {
+    body.trim()
+        .lines()
+        .map(|line| {
+            let text = line.trim_start();
+            let indent = &line[..line.len() - text.len()];
+
+            if let Some(header) = text.strip_prefix('#') {
+                header.trim_start_matches('#').trim().to_string()
+            } else if let Some(item) = ["- ", "* ", "+ "]
+                .iter()
+                .find_map(|marker| text.strip_prefix(marker))
+            {
+                format!("{}{}", indent, item)
+            } else {
+                line.trim_end().to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Full notes for a release, as shown by `cue changelog`
+#[derive(Serialize)]
+pub struct ReleaseNotes {
+    pub version: String,
+    pub url: String,
+
+    /// The notes as published, in markdown
+    pub body: Option<String>,
+}
+
+/// Fetch the notes of release `version`, or of the latest release on `channel`
+pub fn fetch_release_notes(
+    client: &Client,
+    version: Option<&str>,
+    channel: Channel,
+) -> Result<ReleaseNotes, UpgradeError> {
+    let options = UpgradeOptions {
+        version: version.map(str::to_string),
+        channel,
+        ..Default::default()
+    };
+    let release = fetch_target_release(client, &options)?;
+
+    Ok(ReleaseNotes {
+        version: release_version(&release)?.to_string(),
+        url: release.html_url,
+        body: release.body.filter(|b| !b.trim().is_empty()),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_notes_strips_markdown_markers() {
+        let body =
+            "## What's new\n\n- Faster startup\n  * nested item\n+ plus item\nPlain line   \n";
+        assert_eq!(
+            render_notes(body),
+            "What's new\n\nFaster startup\n  nested item\nplus item\nPlain line"
+        );
+    }
+
+    #[test]
+    fn render_notes_keeps_text_that_only_looks_like_a_marker() {
+        assert_eq!(
+            render_notes("-not a list\n*emphasis*"),
+            "-not a list\n*emphasis*"
+        );
+    }
+}
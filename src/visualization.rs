@@ -0,0 +1,99 @@
+use crate::stats::format_size;
+use std::time::Duration;
+
+/// Block characters from lowest to highest amplitude
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Render an amplitude envelope as a single row of `width` block characters.
+///
+/// Samples are split into `width` columns and each column's RMS amplitude is
+/// scaled relative to the loudest column.
+pub fn ascii_waveform(samples: &[f32], width: usize) -> String {
+    if samples.is_empty() || width == 0 {
+        return String::new();
+    }
+
+    let chunk_size = samples.len().div_ceil(width);
+    let levels: Vec<f32> = samples
+        .chunks(chunk_size)
+        .map(|chunk| (chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len() as f32).sqrt())
+        .collect();
+
+    let peak = levels.iter().cloned().fold(0.0, f32::max);
+    if peak == 0.0 {
+        return BARS[0].to_string().repeat(levels.len());
+    }
+
+    levels
+        .iter()
+        .map(|level| {
+            let index = ((level / peak) * (BARS.len() - 1) as f32).round() as usize;
+            BARS[index.min(BARS.len() - 1)]
+        })
+        .collect()
+}
+
+/// Frames of the spinner shown when a total is unknown
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+/// Render playback progress as `[=====>    ] 2.3s / 5.0s`, with `width` columns
+/// between the brackets
+pub fn progress_bar(position: Duration, total: Duration, width: usize) -> String {
+    let fraction = if total.is_zero() {
+        1.0
+    } else {
+        position.as_secs_f64() / total.as_secs_f64()
+    };
+
+    format!(
+        "{} {:.1}s / {:.1}s",
+        bar(fraction, width),
+        position.min(total).as_secs_f64(),
+        total.as_secs_f64()
+    )
+}
+
+/// Render an indeterminate spinner with the elapsed time, advancing one frame per `tick`
+pub fn spinner(position: Duration, tick: usize) -> String {
+    format!(
+        "[{}] {:.1}s",
+        SPINNER[tick % SPINNER.len()],
+        position.as_secs_f64()
+    )
+}
+
+/// Render download progress as `[=====>    ] 1.5 MB / 4.0 MB`
+pub fn download_bar(done: u64, total: u64, width: usize) -> String {
+    let fraction = if total == 0 {
+        1.0
+    } else {
+        done as f64 / total as f64
+    };
+
+    format!(
+        "{} {} / {}",
+        bar(fraction, width),
+        format_size(done.min(total)),
+        format_size(total)
+    )
+}
+
+/// Render an indeterminate spinner with the bytes downloaded so far
+pub fn download_spinner(done: u64, tick: usize) -> String {
+    format!("[{}] {}", SPINNER[tick % SPINNER.len()], format_size(done))
+}
+
+/// `[=====>    ]` with `width` columns between the brackets, filled to `fraction`
+fn bar(fraction: f64, width: usize) -> String {
+    let filled = ((fraction.clamp(0.0, 1.0) * width as f64) as usize).min(width);
+
+    if filled == width {
+        format!("[{}]", "=".repeat(width))
+    } else {
+        format!(
+            "[{}>{}]",
+            "=".repeat(filled),
+            " ".repeat(width - filled - 1)
+        )
+    }
+}